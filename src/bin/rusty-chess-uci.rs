@@ -0,0 +1,7 @@
+//! The engine of `rusty-chess`, speaking UCI to be used from a chess GUI.
+
+use std::io;
+
+fn main() -> io::Result<()> {
+    rusty_chess::protocol::uci::run()
+}
@@ -0,0 +1,7 @@
+//! The engine of `rusty-chess`, speaking CECP to be used from XBoard and similar tools.
+
+use std::io;
+
+fn main() -> io::Result<()> {
+    rusty_chess::protocol::xboard::run()
+}
@@ -0,0 +1,114 @@
+//! Squares attacked by each piece, as bitboards (bit `8 * y + x` is the square `(x, y)`).
+//!
+//! Knights, Kings and pawns jump to fixed squares, precomputed at compile time;
+//! Rooks, Bishops and Queens slide until the first occupied square,
+//! looked up in the [magic bitboards](super::magic).
+
+use {super::magic, crate::types::Color};
+
+/// (x, y) steps of a Knight
+const KNIGHT_STEPS: [(isize, isize); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+/// (x, y) steps of a King, also the directions of the sliding pieces
+const KING_STEPS: [(isize, isize); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+pub(super) const ROOK_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+pub(super) const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+static KNIGHT: [u64; 64] = leaper_table(&KNIGHT_STEPS);
+static KING: [u64; 64] = leaper_table(&KING_STEPS);
+/// Squares attacked by a white pawn and by a black pawn
+static PAWN: [[u64; 64]; 2] = [
+    leaper_table(&[(1, 1), (-1, 1)]),
+    leaper_table(&[(1, -1), (-1, -1)]),
+];
+
+/// Bitboard of the square `(x, y)`, empty if it is outside the board.
+pub(super) const fn bit(x: isize, y: isize) -> u64 {
+    if x >= 0 && x < 8 && y >= 0 && y < 8 {
+        1 << (y * 8 + x)
+    } else {
+        0
+    }
+}
+
+/// Squares reached from each square with one of the `steps`.
+const fn leaper_table(steps: &[(isize, isize)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (x, y) = ((square % 8) as isize, (square / 8) as isize);
+        let mut i = 0;
+        while i < steps.len() {
+            table[square] |= bit(x + steps[i].0, y + steps[i].1);
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+/// Squares reached sliding from `square` in the `directions`,
+/// stopping on (and including) the first square of `occupied`.
+///
+/// This walks the rays square by square, to fill the magic bitboards.
+pub(super) const fn sliding(square: usize, occupied: u64, directions: &[(isize, isize)]) -> u64 {
+    let mut attacks = 0;
+    let mut i = 0;
+    while i < directions.len() {
+        let (dx, dy) = directions[i];
+        let (mut x, mut y) = ((square % 8) as isize + dx, (square / 8) as isize + dy);
+        while bit(x, y) != 0 {
+            attacks |= bit(x, y);
+            if occupied & bit(x, y) != 0 {
+                break;
+            }
+            x += dx;
+            y += dy;
+        }
+        i += 1;
+    }
+    attacks
+}
+
+#[inline]
+pub fn knight(square: usize) -> u64 {
+    KNIGHT[square]
+}
+#[inline]
+pub fn king(square: usize) -> u64 {
+    KING[square]
+}
+/// Squares a pawn of `color` on `square` can eat on.
+#[inline]
+pub fn pawn(color: Color, square: usize) -> u64 {
+    PAWN[usize::from(!bool::from(color))][square]
+}
+#[inline]
+pub fn rook(square: usize, occupied: u64) -> u64 {
+    magic::rook(square, occupied)
+}
+#[inline]
+pub fn bishop(square: usize, occupied: u64) -> u64 {
+    magic::bishop(square, occupied)
+}
+#[inline]
+pub fn queen(square: usize, occupied: u64) -> u64 {
+    rook(square, occupied) | bishop(square, occupied)
+}
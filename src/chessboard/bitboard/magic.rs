@@ -0,0 +1,158 @@
+//! [Magic bitboards](https://www.chessprogramming.org/Magic_Bitboards): the attacks of a Rook
+//! or a Bishop are looked up in a table, indexed by the pieces on its rays multiplied by
+//! a "magic" number which maps every combination of blockers to a different entry.
+//!
+//! The tables are filled on first use.
+
+use {
+    super::attacks::{BISHOP_DIRECTIONS, ROOK_DIRECTIONS, bit, sliding},
+    std::sync::LazyLock,
+};
+
+/// Magic numbers of the Rook on each square, found by trying sparse random numbers
+/// until none of the combinations of blockers giving different attacks collide
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x0980008011400020, 0x8340004410002000, 0x0880200090008268, 0x0080080080100004,
+    0x8100110004020800, 0x0300010004000822, 0x08801A0029000080, 0x8100050001204882,
+    0x0844800081400320, 0x0804402010004000, 0x0108802003100480, 0x0004808008001000,
+    0x0003001801001014, 0x0002000200041008, 0x0004008108042210, 0x0105000100009042,
+    0x0400808000400021, 0xC100404010002000, 0x0060008010002088, 0x0400808008001000,
+    0x4440808008000400, 0x1002008004000280, 0x40024400300D1248, 0x0010020000408104,
+    0x0101008200204200, 0x8020002040005000, 0x4100100080802000, 0x4008006A80100280,
+    0x1020080080040080, 0x0004010040020040, 0x0018A12400080290, 0x6140004200008104,
+    0x4000400020800090, 0x2020002080804000, 0x0000408202002010, 0x0080100501000820,
+    0x0000800400800800, 0x000A200408014010, 0x0100800200800100, 0xA00800570200008C,
+    0x008000406000C010, 0x1040100028002000, 0x0048200100110040, 0x0068490210030020,
+    0x1009080005010010, 0x2142000804010100, 0x1001080110840002, 0x1801004400820001,
+    0x010440208D020200, 0x0000400020008080, 0x0200200080100280, 0x0000100020090100,
+    0x0204008008020480, 0x8104010040020040, 0x78000201B0080400, 0x0040800051002880,
+    0x0050108001002041, 0x208A801100614003, 0x0006002042089082, 0x0011090004201001,
+    0x1002001004200802, 0x0005000208040001, 0x0002002701AC0822, 0x000010250184004A,
+];
+/// Magic numbers of the Bishop on each square, see [`ROOK_MAGICS`]
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0xC0A0012206040EA0, 0x8010228200420001, 0x0110008220400400, 0x02445C0080106000,
+    0x0044042004008100, 0x0880900420408C05, 0x0201080110080002, 0x0000108094202000,
+    0x0000042002040108, 0x0000623024110042, 0x0086100094811002, 0x0000044502002080,
+    0x0100460211400040, 0x0008109004200004, 0x0202320084844000, 0x8040042421041009,
+    0x201010C05102008C, 0x1020888208024080, 0x0108000C80290200, 0x8048000420425203,
+    0x0005000090402000, 0x2080400201104100, 0x8820420111101000, 0x4AC0302208821802,
+    0x000440001002A840, 0x2002200010041080, 0x1012080201004400, 0x8440040002410120,
+    0x1090820084010400, 0x2084852012021000, 0x12040062C1011003, 0x02008205E1090080,
+    0x088C102808042080, 0x0802102200904280, 0x8020209002080020, 0x2200080800060A00,
+    0x20C0004010010100, 0x0802004100821003, 0x0008024400008080, 0x0000840102008090,
+    0x0030A40420244007, 0x0A19084210011282, 0x0004082090019806, 0x6108004208020080,
+    0x0081200410110100, 0x1040810701010208, 0x0282047832012080, 0x0010020099000020,
+    0x000E010422400840, 0x10204208B0089090, 0x081004440C048000, 0x88C0180084040001,
+    0x3100020803040080, 0x890070A041210C00, 0x0020200101010A09, 0x0004100240410400,
+    0x0006004402080200, 0x0801062484042000, 0x00010002D7441004, 0x0810080000208800,
+    0x0000020808030411, 0x1450001020014440, 0x004060081081A288, 0x0044011404108A00,
+];
+
+static MAGICS: LazyLock<Magics> = LazyLock::new(Magics::new);
+
+/// How to find the attacks from a square in [`Magics::table`]
+#[derive(Clone, Copy, Default, Debug)]
+struct Magic {
+    /// Squares whose pieces can block the rays (the last square of each ray never blocks)
+    mask: u64,
+    magic: u64,
+    /// 64 minus the number of squares of `mask`
+    shift: u32,
+    /// Start of the entries of this square
+    offset: usize,
+}
+impl Magic {
+    #[inline(always)]
+    const fn index(&self, occupied: u64) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+#[derive(Debug)]
+struct Magics {
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    /// Attacks of every square and blockers, of both pieces
+    table: Vec<u64>,
+}
+
+impl Magics {
+    fn new() -> Self {
+        let mut table = Vec::new();
+        let mut magics = |numbers: &[u64; 64], directions: &[(isize, isize)]| {
+            let mut magics = [Magic::default(); 64];
+            for (square, magic) in magics.iter_mut().enumerate() {
+                *magic = fill(square, numbers[square], directions, &mut table);
+            }
+            magics
+        };
+        let rook = magics(&ROOK_MAGICS, &ROOK_DIRECTIONS);
+        let bishop = magics(&BISHOP_MAGICS, &BISHOP_DIRECTIONS);
+        Self {
+            rook,
+            bishop,
+            table,
+        }
+    }
+}
+
+/// Squares of the rays from `square` which can hold a blocker.
+fn blockers_mask(square: usize, directions: &[(isize, isize)]) -> u64 {
+    let (x, y) = ((square % 8) as isize, (square / 8) as isize);
+    let mut mask = 0;
+    for (dx, dy) in directions {
+        let (mut x, mut y) = (x + dx, y + dy);
+        while bit(x + dx, y + dy) != 0 {
+            mask |= bit(x, y);
+            x += dx;
+            y += dy;
+        }
+    }
+    mask
+}
+
+/// Appends to `table` the attacks from `square` with every combination of blockers.
+fn fill(square: usize, magic: u64, directions: &[(isize, isize)], table: &mut Vec<u64>) -> Magic {
+    let mask = blockers_mask(square, directions);
+    let bits = mask.count_ones();
+    let magic = Magic {
+        mask,
+        magic,
+        shift: 64 - bits,
+        offset: table.len(),
+    };
+    table.resize(table.len() + (1 << bits), 0);
+
+    // every subset of the mask (Carry-Rippler)
+    let mut blockers: u64 = 0;
+    loop {
+        let attacks = sliding(square, blockers, directions);
+        let slot = &mut table[magic.index(blockers)];
+        // sets of blockers may share a slot only if they give the same attacks
+        debug_assert!(
+            *slot == 0 || *slot == attacks,
+            "magic {magic:#x} of square {square} collides",
+            magic = magic.magic
+        );
+        *slot = attacks;
+        blockers = blockers.wrapping_sub(mask) & mask;
+        if blockers == 0 {
+            break;
+        }
+    }
+    magic
+}
+
+/// Squares attacked by a Rook on `square`.
+#[inline]
+pub fn rook(square: usize, occupied: u64) -> u64 {
+    MAGICS.table[MAGICS.rook[square].index(occupied)]
+}
+/// Squares attacked by a Bishop on `square`.
+#[inline]
+pub fn bishop(square: usize, occupied: u64) -> u64 {
+    MAGICS.table[MAGICS.bishop[square].index(occupied)]
+}
@@ -0,0 +1,423 @@
+//! A [`Board`] backend made of [bitboards](https://www.chessprogramming.org/Bitboards):
+//! a 64-bit set of squares for each kind of piece of each color.
+//!
+//! [`BitBoard`] is [`Copy`] and generates the same moves as [`Board`]
+//! without allocating any piece.
+//!
+//! It is a separate backend, converted from a [`Board`] by walking its squares:
+//! the [`Board`] doesn't keep one up to date as its moves are made.
+//! It is used to count the moves with [`BitBoard::perft`] and check them against
+//! the ones of [`Board`], and as a snapshot of the pieces to evaluate a position.
+
+pub mod attacks;
+mod magic;
+#[cfg(test)]
+mod tests;
+
+use {
+    crate::{
+        chessboard::{Board, PROMOTIONS},
+        geomath::{Point, rotation::Direction},
+        types::*,
+    },
+    attacks::bit,
+    indexmap::IndexSet,
+};
+
+/// Letters of the kinds of pieces, in the order of [`BitBoard`]'s bitboards
+const KINDS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+const PAWN: usize = 0;
+const KNIGHT: usize = 1;
+const BISHOP: usize = 2;
+const ROOK: usize = 3;
+const QUEEN: usize = 4;
+const KING: usize = 5;
+
+/// Castles in the order of [`BitBoard`]'s castling rights
+const CASTLES: [SpecialMove; 2] = [SpecialMove::ShortCastle, SpecialMove::LongCastle];
+
+/// Index of the bitboards of a color
+#[inline(always)]
+const fn side(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+/// Files where the King and the Rook land with a castle.
+#[inline]
+const fn castled_files(castle: &SpecialMove) -> (isize, isize) {
+    match castle {
+        SpecialMove::ShortCastle => (King::SHORT_CASTLE_FILE, King::SHORT_CASTLE_FILE - 1),
+        _ => (King::LONG_CASTLE_FILE, King::LONG_CASTLE_FILE + 1),
+    }
+}
+/// Index of a square in a bitboard
+#[inline(always)]
+pub(super) const fn square(pos: Point) -> usize {
+    (pos.y * Board::SIZE as isize + pos.x) as usize
+}
+#[inline(always)]
+pub(super) const fn point(square: usize) -> Point {
+    Point::new(
+        (square % Board::SIZE) as isize,
+        (square / Board::SIZE) as isize,
+    )
+}
+/// Squares of a bitboard, from a1 to h8
+pub(super) fn squares(mut bitboard: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (bitboard != 0).then(|| {
+            let square = bitboard.trailing_zeros() as usize;
+            bitboard &= bitboard - 1;
+            square
+        })
+    })
+}
+
+/// A position stored as bitboards, converted from a [`Board`]
+/// (once per position, as the conversion walks the whole grid).
+///
+/// It has the same move generation API as [`Board`]:
+/// [`BitBoard::filtered_move_set`], [`BitBoard::all_moves`] and [`BitBoard::check`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BitBoard {
+    /// Squares of each kind of piece (see [`KINDS`]) of White and Black
+    pieces: [[u64; 6]; 2],
+    /// Files of the Rooks White and Black can still castle short and long with
+    castling: [[Option<isize>; 2]; 2],
+    /// The pawn which has just done a double move, see [`PawnState::JustDouble`]
+    en_passant: Option<Point>,
+}
+
+impl Default for BitBoard {
+    #[inline]
+    fn default() -> Self {
+        Self::from(&Board::default())
+    }
+}
+
+impl From<&Board> for BitBoard {
+    fn from(board: &Board) -> Self {
+        let mut bitboard = Self {
+            pieces: [[0; 6]; 2],
+            castling: [[None; 2]; 2],
+            en_passant: None,
+        };
+        let squares = board.iter().flatten().enumerate();
+        for (square, piece) in squares.filter_map(|(i, square)| Some((i, square.as_deref()?))) {
+            let pos = point(square);
+            let kind = KINDS.iter().position(|c| *c == piece.symbol()).unwrap();
+            bitboard.pieces[side(piece.color())][kind] |= bit(pos.x, pos.y);
+            if piece.as_any().is::<Pawn>() && piece.is_state(PawnState::JustDouble.into()) {
+                bitboard.en_passant = Some(pos);
+            }
+        }
+        for color in [Color::White, Color::Black] {
+            for (i, castle) in CASTLES.iter().enumerate() {
+                bitboard.castling[side(color)][i] =
+                    board.castling_rook(color, castle).map(|rook| rook.x);
+            }
+        }
+        bitboard
+    }
+}
+
+impl BitBoard {
+    /// Squares of the pieces of `color`
+    #[inline]
+    fn occupancy(&self, color: Color) -> u64 {
+        self.pieces[side(color)]
+            .iter()
+            .fold(0, |all, kind| all | kind)
+    }
+    /// Squares of all the pieces
+    #[inline]
+    fn occupied(&self) -> u64 {
+        self.occupancy(Color::White) | self.occupancy(Color::Black)
+    }
+    /// Color and index in [`KINDS`] of the piece on a square.
+    fn piece_on(&self, square: usize) -> Option<(Color, usize)> {
+        [Color::White, Color::Black].into_iter().find_map(|color| {
+            self.pieces[side(color)]
+                .iter()
+                .position(|kind| kind & (1 << square) != 0)
+                .map(|kind| (color, kind))
+        })
+    }
+    /// Color and letter (see [`Piece::symbol`]) of the piece on `pos`.
+    #[inline]
+    pub fn get(&self, pos: Point) -> Option<(Color, char)> {
+        self.piece_on(square(pos))
+            .map(|(color, kind)| (color, KINDS[kind]))
+    }
+    /// Takes the piece on `pos` off the board, if there is one.
+    ///
+    /// The pieces it was hiding can then attack through its square.
+    #[inline]
+    pub fn remove(&mut self, pos: Point) {
+        for kind in self.pieces.iter_mut().flatten() {
+            *kind &= !(1 << square(pos));
+        }
+    }
+    /// Squares attacked by the piece of `kind` and `color` on `square`.
+    fn attacks(&self, color: Color, kind: usize, square: usize) -> u64 {
+        match kind {
+            PAWN => attacks::pawn(color, square),
+            KNIGHT => attacks::knight(square),
+            BISHOP => attacks::bishop(square, self.occupied()),
+            ROOK => attacks::rook(square, self.occupied()),
+            QUEEN => attacks::queen(square, self.occupied()),
+            _ => attacks::king(square),
+        }
+    }
+    /// Pieces of color `by` which could eat a piece on `target`.
+    fn attackers(&self, target: usize, by: Color) -> u64 {
+        let occupied = self.occupied();
+        let pieces = &self.pieces[side(by)];
+        let straight = pieces[ROOK] | pieces[QUEEN];
+        let diagonal = pieces[BISHOP] | pieces[QUEEN];
+
+        // a pawn of `by` attacks `target` if a pawn on `target` would attack it back
+        (attacks::pawn(by.opposite(), target) & pieces[PAWN])
+            | (attacks::knight(target) & pieces[KNIGHT])
+            | (attacks::king(target) & pieces[KING])
+            | (attacks::rook(target, occupied) & straight)
+            | (attacks::bishop(target, occupied) & diagonal)
+    }
+    /// Coordinates of the pieces of color `by` which could eat a piece on `target`,
+    /// like [`Board::attackers_of`].
+    #[inline]
+    pub fn attackers_of(&self, target: Point, by: Color) -> IndexSet<Point> {
+        squares(self.attackers(square(target), by))
+            .map(point)
+            .collect()
+    }
+    /// Whether a piece of color `by` could eat a piece on `target`.
+    #[inline]
+    pub fn is_attacked(&self, target: Point, by: Color) -> bool {
+        self.attackers(square(target), by) != 0
+    }
+    /// The moves of the piece on `from`, like [`Board::filtered_move_set`]:
+    /// collisions are handled, but the moves may leave the King in check.
+    ///
+    /// Panics if there is no piece on `from`.
+    pub fn filtered_move_set(&self, from: Point) -> IndexSet<Movement> {
+        let (color, kind) = self.piece_on(square(from)).unwrap();
+        let own = self.occupancy(color);
+        let enemy = self.occupancy(color.opposite());
+        let empty = !(own | enemy);
+        let mut moves = IndexSet::new();
+
+        if kind == PAWN {
+            let sign = color.sign();
+            let forward = from + Point::new(0, sign);
+            if bit(forward.x, forward.y) & empty != 0 {
+                moves.insert(Movement::new(
+                    from,
+                    forward,
+                    Some(SpecialMove::CannotEat),
+                    Some(Direction::Up),
+                ));
+                let double = forward + Point::new(0, sign);
+                if from.y == color.second_row() as isize && bit(double.x, double.y) & empty != 0 {
+                    moves.insert(Movement::new(
+                        from,
+                        double,
+                        Some(SpecialMove::DoublePawn),
+                        Some(Direction::Up),
+                    ));
+                }
+            }
+            let en_passant = self
+                .en_passant
+                .filter(|pawn| self.get(*pawn).is_some_and(|(c, _)| c != color))
+                .map_or(0, |pawn| bit(pawn.x, pawn.y + sign));
+            for to in squares(attacks::pawn(color, square(from)) & (enemy | en_passant)) {
+                let to = point(to);
+                // directions are seen from the side of the pawn
+                let direction = if (to.x - from.x) * sign == 1 {
+                    Direction::UpRight
+                } else {
+                    Direction::UpLeft
+                };
+                moves.insert(Movement::new(
+                    from,
+                    to,
+                    Some(SpecialMove::PawnEat),
+                    Some(direction),
+                ));
+            }
+            return moves;
+        }
+
+        for to in squares(self.attacks(color, kind, square(from)) & !own) {
+            let to = point(to);
+            moves.insert(Movement::new(from, to, None, (to - from).into()));
+        }
+        if kind == KING {
+            let row = from.y;
+            // squares of the files from `a` to `b`, both included
+            let span =
+                |a: isize, b: isize| (a.min(b)..=a.max(b)).fold(0, |all, x| all | bit(x, row));
+            for (i, castle) in CASTLES.into_iter().enumerate() {
+                let Some(rook) = self.castling[side(color)][i] else {
+                    continue;
+                };
+                let (king_to, rook_to) = castled_files(&castle);
+                let king_path = span(from.x, king_to);
+                // the King and the Rook only need each other's square to be empty,
+                // and the King can't castle out of, through or into a check
+                if (king_path | span(rook, rook_to)) & !empty & !(bit(from.x, row) | bit(rook, row))
+                    == 0
+                    && !squares(king_path).any(|x| self.attackers(x, color.opposite()) != 0)
+                {
+                    let direction = if castle == SpecialMove::ShortCastle {
+                        Direction::Right
+                    } else {
+                        Direction::Left
+                    };
+                    moves.insert(Movement::new(
+                        from,
+                        Point::new(king_to, row),
+                        Some(castle),
+                        Some(direction),
+                    ));
+                }
+            }
+        }
+        moves
+    }
+    /// Returns all the moves a player (`color`) can do, like [`Board::all_moves`].
+    pub fn all_moves(&self, color: Color) -> IndexSet<Movement> {
+        squares(self.occupancy(color))
+            .flat_map(|from| self.filtered_move_set(point(from)))
+            .collect()
+    }
+    /// A move of the other player eating the King of `color`, if it is in check.
+    pub fn check(&self, color: Color) -> Option<Movement> {
+        let king = squares(self.pieces[side(color)][KING]).next()?;
+        let from = squares(self.attackers(king, color.opposite())).next()?;
+        let (from, to) = (point(from), point(king));
+
+        Some(
+            if self.pieces[side(color.opposite())][PAWN] & (1 << square(from)) != 0 {
+                let direction = if (to.x - from.x) * color.opposite().sign() == 1 {
+                    Direction::UpRight
+                } else {
+                    Direction::UpLeft
+                };
+                Movement::new(from, to, Some(SpecialMove::PawnEat), Some(direction))
+            } else {
+                Movement::new(from, to, None, (to - from).into())
+            },
+        )
+    }
+    /// Whether a move returned by [`BitBoard::filtered_move_set`]
+    /// doesn't leave the King of the player who does it in check.
+    pub fn is_legal(&self, mov: &Movement) -> bool {
+        let (color, _) = self.piece_on(square(mov.from)).unwrap();
+        let mut bitboard = *self;
+        bitboard.make_move(mov, None);
+        bitboard.check(color).is_none()
+    }
+    /// Does a move returned by [`BitBoard::filtered_move_set`].
+    ///
+    /// `promotion` is the letter (B/N/R/Q) of the piece a promoting pawn becomes,
+    /// a Queen if it is `None`.
+    ///
+    /// > There is no unmake: a [`BitBoard`] is small enough to be copied before the move.
+    pub fn make_move(&mut self, mov: &Movement, promotion: Option<char>) {
+        let (from, to) = (square(mov.from), square(mov.to));
+        let (color, kind) = self.piece_on(from).unwrap();
+        let us = side(color);
+        let them = side(color.opposite());
+
+        // the eaten piece (behind the pawn for En Passant)
+        let captured = match mov.special {
+            Some(SpecialMove::PawnEat) if self.piece_on(to).is_none() => {
+                to as isize - color.sign() * Board::SIZE as isize
+            }
+            _ => to as isize,
+        } as usize;
+        for bitboard in &mut self.pieces[them] {
+            *bitboard &= !(1 << captured);
+        }
+
+        let arrived = if kind == PAWN && mov.to.y == color.opposite().first_row() as isize {
+            let letter = promotion.unwrap_or('Q').to_ascii_uppercase();
+            KINDS.iter().position(|c| *c == letter).unwrap()
+        } else {
+            kind
+        };
+        self.pieces[us][kind] &= !(1 << from);
+        self.pieces[us][arrived] |= 1 << to;
+
+        if let Some(castle @ (SpecialMove::ShortCastle | SpecialMove::LongCastle)) = &mov.special {
+            let row = mov.from.y;
+            let i = CASTLES.iter().position(|c| c == castle).unwrap();
+            let rook = self.castling[us][i].unwrap();
+            let (_, rook_to) = castled_files(castle);
+            // the King may land on the square of the Rook (Chess960)
+            self.pieces[us][ROOK] &= !bit(rook, row);
+            self.pieces[us][ROOK] |= bit(rook_to, row);
+        }
+
+        // a King or a Rook leaving its square, or a Rook eaten, ends the right to castle
+        if kind == KING {
+            self.castling[us] = [None; 2];
+        }
+        for color in [Color::White, Color::Black] {
+            let row = color.first_row() as isize;
+            for right in &mut self.castling[side(color)] {
+                if right.is_some_and(|rook| [mov.from, mov.to].contains(&Point::new(rook, row))) {
+                    *right = None;
+                }
+            }
+        }
+
+        self.en_passant = (mov.special == Some(SpecialMove::DoublePawn)).then_some(mov.to);
+    }
+    /// Returns the moves `color` can play, like [`Board::legal_moves`].
+    pub fn legal_moves(&self, color: Color) -> IndexSet<Movement> {
+        self.all_moves(color)
+            .into_iter()
+            .filter(|mov| self.is_legal(mov))
+            .collect()
+    }
+    /// Legal moves of `color`, with a move for each piece a pawn can promote to.
+    fn legal_choices(&self, color: Color) -> Vec<(Movement, Option<char>)> {
+        self.legal_moves(color)
+            .into_iter()
+            .flat_map(|mov| {
+                let promoting = self.get(mov.from).is_some_and(|(_, c)| c == 'P')
+                    && mov.to.y == color.opposite().first_row() as isize;
+                let promotions = if promoting {
+                    PROMOTIONS.map(Some).to_vec()
+                } else {
+                    vec![None]
+                };
+                promotions
+                    .into_iter()
+                    .map(move |promotion| (mov.clone(), promotion))
+            })
+            .collect()
+    }
+    /// [`Board::perft`] on the bitboards.
+    pub fn perft(&self, color: Color, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        self.legal_choices(color)
+            .into_iter()
+            .map(|(mov, promotion)| {
+                if depth == 1 {
+                    return 1;
+                }
+                let mut bitboard = *self;
+                bitboard.make_move(&mov, promotion);
+                bitboard.perft(color.opposite(), depth - 1)
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,130 @@
+use {
+    crate::{chessboard::bitboard::attacks, prelude::*},
+    std::error::Error,
+};
+
+const POSITIONS: [&str; 7] = [
+    START_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    // Chess960: the Kings and the Rooks swap squares or the Kings move towards the Rooks
+    "rk5r/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 w GBha - 0 1",
+];
+
+/// Checks that both backends agree on `board` and on the positions after each legal move,
+/// `depth` moves deep.
+fn assert_same_moves(board: &mut Board, color: Color, depth: usize) {
+    let bitboard = BitBoard::from(&*board);
+    for color in [Color::White, Color::Black] {
+        assert_eq!(bitboard.all_moves(color), board.all_moves(color));
+        assert_eq!(
+            bitboard.check(color).is_some(),
+            board.check(color).is_some()
+        );
+    }
+    if depth == 0 {
+        return;
+    }
+    for (mov, promotion) in board.legal_choices(color) {
+        assert!(bitboard.is_legal(&mov));
+        let mut after = bitboard;
+        after.make_move(&mov, promotion);
+
+        let undo = board.make_move_promoting(&mov, promotion);
+        assert_eq!(after, BitBoard::from(&*board), "{}", mov.to_uci(promotion));
+        assert_same_moves(board, color.opposite(), depth - 1);
+        board.unmake_move(undo);
+    }
+}
+
+#[test]
+fn same_moves_as_board() -> Result<(), Box<dyn Error>> {
+    for fen in POSITIONS {
+        let mut game = Game::from_fen(fen)?;
+        assert_same_moves(&mut game.board, game.turn, 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn leaper_attacks() {
+    // knight on a1: b3 and c2
+    assert_eq!(attacks::knight(0), 1 << 17 | 1 << 10);
+    // king on h8: g8, g7 and h7
+    assert_eq!(attacks::king(63).count_ones(), 3);
+    // white pawn on e2: d3 and f3
+    assert_eq!(attacks::pawn(Color::White, 12), 1 << 19 | 1 << 21);
+    assert_eq!(attacks::pawn(Color::Black, 12), 1 << 3 | 1 << 5);
+}
+
+#[test]
+fn sliding_attacks() {
+    // rook on a1, blocked on a4 and d1
+    let occupied = 1 << 24 | 1 << 3;
+    assert_eq!(
+        attacks::rook(0, occupied),
+        1 << 8 | 1 << 16 | 1 << 24 | 1 << 1 | 1 << 2 | 1 << 3
+    );
+    assert_eq!(attacks::bishop(0, 0).count_ones(), 7);
+    assert_eq!(attacks::queen(27, 0).count_ones(), 27);
+}
+
+#[test]
+fn get() -> Result<(), Box<dyn Error>> {
+    let bitboard = BitBoard::default();
+    assert_eq!(
+        bitboard.get(Point::try_from("e1")?),
+        Some((Color::White, 'K'))
+    );
+    assert_eq!(
+        bitboard.get(Point::try_from("d8")?),
+        Some((Color::Black, 'Q'))
+    );
+    assert_eq!(bitboard.get(Point::try_from("e4")?), None);
+    Ok(())
+}
+
+#[test]
+fn promotion() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen("8/P7/8/8/8/8/k7/4K3 w - - 0 1")?;
+    let mut bitboard = BitBoard::from(&game.board);
+    let mov = Movement::from_uci(&game.board, Color::White, "a7a8n")?.0;
+    bitboard.make_move(&mov, Some('N'));
+    assert_eq!(
+        bitboard.get(Point::try_from("a8")?),
+        Some((Color::White, 'N'))
+    );
+    // a Queen by default
+    let mut bitboard = BitBoard::from(&game.board);
+    bitboard.make_move(&mov, None);
+    assert_eq!(
+        bitboard.get(Point::try_from("a8")?),
+        Some((Color::White, 'Q'))
+    );
+    Ok(())
+}
+
+#[test]
+fn magic_attacks() {
+    // the lookups give the same attacks as walking the rays, whatever the blockers
+    let mut occupied: u64 = 0x9E37_79B9_7F4A_7C15;
+    for square in 0..64 {
+        for _ in 0..64 {
+            occupied ^= occupied << 13;
+            occupied ^= occupied >> 7;
+            occupied ^= occupied << 17;
+            let blockers = occupied & occupied.rotate_left(17);
+            assert_eq!(
+                attacks::rook(square, blockers),
+                attacks::sliding(square, blockers, &attacks::ROOK_DIRECTIONS)
+            );
+            assert_eq!(
+                attacks::bishop(square, blockers),
+                attacks::sliding(square, blockers, &attacks::BISHOP_DIRECTIONS)
+            );
+        }
+    }
+}
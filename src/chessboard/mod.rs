@@ -11,41 +11,67 @@ use {
     },
 };
 
+pub mod bitboard;
+mod perft;
 #[cfg(test)]
 mod tests;
+pub mod zobrist;
+
+pub use bitboard::BitBoard;
+
+/// Letters of the pieces a pawn can promote to
+const PROMOTIONS: [char; 4] = ['N', 'B', 'R', 'Q'];
 
 pub type Square = Option<Box<dyn Piece>>;
 pub type Row = [Square; Board::SIZE];
 pub type Grid = [Row; Board::SIZE];
 
 #[derive(Debug)]
-pub struct Board(Grid);
+pub struct Board {
+    grid: Grid,
+    /// Zobrist hash of the position, see [`Board::zobrist`]
+    hash: u64,
+    /// Squares of all the pieces, see [`Board::occupied`]
+    occupied: Option<u64>,
+}
+
+/// What a move destroys on the [`Board`], returned by [`Board::make_move`]
+/// to take it back with [`Board::unmake_move`].
+#[derive(Debug)]
+pub struct UndoInfo {
+    /// The move done
+    pub movement: Movement,
+    /// Letter of the piece the pawn promoted to
+    pub promotion: Option<char>,
+    /// The piece eaten by the move and where it was (not [`Movement::to`] for En Passant)
+    pub captured: Option<(Point, Box<dyn Piece>)>,
+    /// The moved piece as it was before the move (state included), the pawn if it promoted
+    moved: Box<dyn Piece>,
+    /// The Rook moved by a castle as it was before the move
+    rook: Option<Box<dyn Piece>>,
+    /// Pawns that could suffer an En Passant before the move
+    just_double: Vec<Point>,
+    /// [Zobrist hash](Board::zobrist) before the move
+    hash: u64,
+}
 
 impl Clone for Board {
     fn clone(&self) -> Self {
         let mut board = Self::empty();
         for (y, row) in self.iter().enumerate() {
             for (x, square) in row.iter().enumerate() {
-                board[Point::new(x as isize, y as isize)] = square.map(Piece::clone_box);
+                board[Point::new(x as isize, y as isize)] = square.as_deref().map(Piece::clone_box);
             }
         }
+        board.hash = self.hash;
+        board.occupied = self.occupied;
         board
     }
 }
 impl Default for Board {
+    #[inline]
     fn default() -> Self {
-        let mut board = Self::empty();
-
-        for color in [Color::White, Color::Black] {
-            for x in 0..Board::SIZE as isize {
-                let coord = Point::new(x, color.second_row() as isize);
-                board[coord] = Some(Box::new(Pawn::new(color, coord)));
-
-                board[Point::new(x, color.first_row() as isize)] = placement(x, color);
-            }
-        }
-
-        board
+        Self::chess960(CLASSICAL_SETUP)
     }
 }
 impl Index<Point> for Board {
@@ -53,32 +79,35 @@ impl Index<Point> for Board {
     #[inline]
     fn index(&self, index: Point) -> &Self::Output {
         assert!(Self::in_bounds(index), "(x, y): {index}");
-        &self.0[index.y as usize][index.x as usize]
+        &self.grid[index.y as usize][index.x as usize]
     }
 }
+/// Changing a square by hand makes the board forget which squares are occupied,
+/// until [`Board::rehash`].
 impl IndexMut<Point> for Board {
     #[inline]
     fn index_mut(&mut self, index: Point) -> &mut Self::Output {
-        assert!(Self::in_bounds(index), "(x, y): {index}");
-        &mut self.0[index.y as usize][index.x as usize]
+        self.occupied = None;
+        self.square_mut(index)
     }
 }
 impl Index<usize> for Board {
     type Output = Row;
     #[inline(always)]
     fn index(&self, index: usize) -> &Self::Output {
-        &self.0[index]
+        &self.grid[index]
     }
 }
 impl IndexMut<usize> for Board {
     #[inline(always)]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.0[index]
+        self.occupied = None;
+        &mut self.grid[index]
     }
 }
 impl Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, row) in self.0.iter().rev().enumerate() {
+        for (i, row) in self.grid.iter().rev().enumerate() {
             write!(f, "{} ", Board::SIZE - i)?;
             for (j, cell) in row.iter().enumerate() {
                 let colored = match cell {
@@ -106,7 +135,31 @@ impl Board {
     /// Constructor of `Board` returning all of its squares as `None`
     #[inline]
     pub fn empty() -> Self {
-        Self(Grid::default())
+        Self {
+            grid: Grid::default(),
+            hash: 0,
+            occupied: Some(0),
+        }
+    }
+    /// Constructor of `Board` with the [Chess960](chess960_rank) setup numbered `index`
+    /// ([`CLASSICAL_SETUP`] is the classical one).
+    ///
+    /// Panics if `index` is not less than [`CHESS960_POSITIONS`].
+    pub fn chess960(index: usize) -> Self {
+        let mut board = Self::empty();
+
+        for color in [Color::White, Color::Black] {
+            for x in 0..Board::SIZE as isize {
+                let coord = Point::new(x, color.second_row() as isize);
+                board[coord] = Some(Box::new(Pawn::new(color, coord)));
+
+                board[Point::new(x, color.first_row() as isize)] =
+                    chess960_placement(index, x, color);
+            }
+        }
+
+        board.rehash();
+        board
     }
     /// Checks if a `Point` is inside the Board.
     #[inline]
@@ -118,17 +171,58 @@ impl Board {
     }
     #[inline]
     pub fn get(&self, point: Point) -> Option<&Square> {
-        Board::in_bounds(point).then_some(&self[point])
+        Board::in_bounds(point).then(|| &self[point])
     }
     #[inline(always)]
     pub fn iter(&self) -> impl Iterator<Item = &Row> {
-        self.0.iter()
+        self.grid.iter()
+    }
+    /// Squares of all the pieces, as a bitboard (see [`bitboard`]).
+    ///
+    /// It is kept up to date by the moves, and found again from the squares
+    /// only after they have been changed by hand (until [`Board::rehash`]).
+    #[inline]
+    fn occupied(&self) -> u64 {
+        self.occupied.unwrap_or_else(|| {
+            self.all_pieces()
+                .into_iter()
+                .fold(0, |occupied, pos| occupied | 1 << bitboard::square(pos))
+        })
+    }
+    /// A square, changed by the moves which keep [`Board::occupied`] up to date
+    /// with [`Board::set_occupied`].
+    #[inline]
+    fn square_mut(&mut self, pos: Point) -> &mut Square {
+        assert!(Self::in_bounds(pos), "(x, y): {pos}");
+        &mut self.grid[pos.y as usize][pos.x as usize]
+    }
+    /// Puts `square` on `pos`, keeping [`Board::occupied`] up to date,
+    /// and returns what was there.
+    #[inline]
+    fn set_square(&mut self, pos: Point, square: Square) -> Square {
+        self.set_occupied(pos, square.is_some());
+        std::mem::replace(self.square_mut(pos), square)
+    }
+    /// Marks `pos` as occupied or free in [`Board::occupied`].
+    #[inline]
+    fn set_occupied(&mut self, pos: Point, occupied: bool) {
+        if let Some(squares) = &mut self.occupied {
+            let bit = 1 << bitboard::square(pos);
+            *squares = if occupied {
+                *squares | bit
+            } else {
+                *squares & !bit
+            };
+        }
     }
     /// From the normal `.move_set()`, returns only the possible moves,
     /// filtering:
-    /// - **(1° .filter())**: Collisions (for bishops, pawns, rooks and queens)
+    /// - **(1° .filter())**: Collisions (for pawns and kings)
     /// - **(2° .filter())**: Impossible SpecialMoves
     ///
+    /// The moves of Bishops, Rooks and Queens are looked up in
+    /// [magic bitboards](bitboard::attacks) instead, which stop at the first piece of each ray.
+    ///
     /// The `from` parameter is the `Movement.from` field.
     pub fn filtered_move_set(&self, from: Point) -> IndexSet<Movement> {
         let mut ignored = HashSet::new();
@@ -136,10 +230,33 @@ impl Board {
         // .unwrap() checks that the piece exists
         let piece = self[from].as_ref().unwrap();
 
+        let square = bitboard::square(from);
+        let sliding = match piece.symbol() {
+            'B' => Some(bitboard::attacks::bishop(square, self.occupied())),
+            'R' => Some(bitboard::attacks::rook(square, self.occupied())),
+            'Q' => Some(bitboard::attacks::queen(square, self.occupied())),
+            _ => None,
+        };
+        if let Some(attacks) = sliding {
+            return bitboard::squares(attacks)
+                .map(bitboard::point)
+                .filter(|to| {
+                    self[*to]
+                        .as_ref()
+                        .is_none_or(|new_piece| new_piece.color() != piece.color())
+                })
+                .map(|to| Movement::new(from, to, None, (to - from).into()))
+                .collect();
+        }
+
         piece
             .move_set()
             .into_iter()
             .filter(|mov| {
+                // the squares of a castle are checked with the special move
+                if mov.is_castle() {
+                    return true;
+                }
                 let Some(direction) = mov.direction else {
                     return self[mov.to]
                         .as_ref()
@@ -184,7 +301,9 @@ impl Board {
                                 else {
                                     return false;
                                 };
-                                if !new_piece.is_state(State::PawnState(PawnState::JustDouble)) {
+                                if !new_piece.as_any().is::<Pawn>()
+                                    || !new_piece.is_state(State::PawnState(PawnState::JustDouble))
+                                {
                                     return false;
                                 }
                                 new_piece
@@ -193,64 +312,61 @@ impl Board {
                         new_piece.color() != piece.color()
                     }
 
-                    SpecialMove::ShortCastle => {
-                        let rook_pos = mov.from + Point::new(3, 0);
-                        if !Board::in_bounds(rook_pos) {
-                            return false;
-                        }
-                        let Some(rook) = self[rook_pos].as_ref() else {
+                    SpecialMove::ShortCastle | SpecialMove::LongCastle => {
+                        let Some(rook_pos) = self.castling_rook(piece.color(), special) else {
                             return false;
                         };
-
-                        let start = mov.from.x as usize + 1;
-                        let end = rook_pos.x as usize;
-
-                        let slice = &self[piece.color().first_row()][start..end];
-
-                        piece.is_state(State::PieceState(PieceState::NotYet))
-                            && rook.is_state(State::PieceState(PieceState::NotYet))
-                            && slice.iter().all(Option::is_none)
-                            && !self
-                                .all_moves(piece.color().opposite())
-                                .into_iter()
-                                .any(|mov| {
-                                    (0..2)
-                                        .map(|i| piece.pos() + Point::new(i, 0))
-                                        .any(|p| mov.to == p)
-                                })
-                    }
-
-                    SpecialMove::LongCastle => {
-                        let rook_pos = mov.from + Point::new(-4, 0);
-                        if !Board::in_bounds(rook_pos) {
-                            return false;
-                        }
-                        let Some(rook) = self[rook_pos].as_ref() else {
-                            return false;
-                        };
-                        let start = rook_pos.x as usize + 1;
-                        let end = mov.from.x as usize;
-                        let slice = &self[piece.color().first_row()][start..end];
-
-                        piece.is_state(State::PieceState(PieceState::NotYet))
-                            && rook.is_state(State::PieceState(PieceState::NotYet))
-                            && slice.iter().all(Option::is_none)
-                            && !self
-                                .all_moves(piece.color().opposite())
-                                .into_iter()
-                                .any(|mov| {
-                                    (-2..0)
-                                        .map(|i| piece.pos() + Point::new(i, 0))
-                                        .any(|p| mov.to == p)
-                                })
+                        let rook_to = Self::castled_rook_pos(mov);
+                        // files from `a` to `b`, both included
+                        let span = |a: isize, b: isize| a.min(b)..=a.max(b);
+                        let row = |x| Point::new(x, mov.from.y);
+
+                        // the King and the Rook only need each other's square to be empty,
+                        // and the King can't castle out of, through or into a check
+                        span(mov.from.x, mov.to.x)
+                            .chain(span(rook_pos.x, rook_to.x))
+                            .map(row)
+                            .all(|p| p == mov.from || p == rook_pos || self[p].is_none())
+                            && !span(mov.from.x, mov.to.x)
+                                .map(row)
+                                .any(|p| self.is_attacked(p, piece.color().opposite()))
                     }
                 }
             })
             .collect()
     }
-    /// Coordinates of all pieces on the board
-    pub fn all_pieces(&self) -> HashSet<Point> {
-        let mut set = HashSet::new();
+    /// Coordinates of the pieces of color `by` which could eat a piece on `target`.
+    ///
+    /// Unlike [`Board::all_moves`], pawns only attack diagonally
+    /// (their pushes and En Passant are not attacks) and Kings can't castle.
+    /// The pieces attacking `target` don't need to be able to move legally.
+    pub fn attackers_of(&self, target: Point, by: Color) -> IndexSet<Point> {
+        let target = 1 << bitboard::square(target);
+        let occupied = self.occupied();
+        self.all_color_pieces(by)
+            .into_iter()
+            .filter(|pos| {
+                let square = bitboard::square(*pos);
+                let attacks = match self[*pos].as_ref().unwrap().symbol() {
+                    'P' => bitboard::attacks::pawn(by, square),
+                    'N' => bitboard::attacks::knight(square),
+                    'B' => bitboard::attacks::bishop(square, occupied),
+                    'R' => bitboard::attacks::rook(square, occupied),
+                    'Q' => bitboard::attacks::queen(square, occupied),
+                    _ => bitboard::attacks::king(square),
+                };
+                attacks & target != 0
+            })
+            .collect()
+    }
+    /// Whether a piece of color `by` could eat a piece on `target`, see [`Board::attackers_of`].
+    #[inline]
+    pub fn is_attacked(&self, target: Point, by: Color) -> bool {
+        !self.attackers_of(target, by).is_empty()
+    }
+    /// Coordinates of all pieces on the board, from `a1` to `h1` then row by row up to `h8`
+    pub fn all_pieces(&self) -> IndexSet<Point> {
+        let mut set = IndexSet::new();
         for (y, row) in self.iter().enumerate() {
             for (x, square) in row.iter().enumerate() {
                 if square.is_some() {
@@ -260,8 +376,9 @@ impl Board {
         }
         set
     }
-    /// Coordinates of all pieces with a given color on the board
-    fn all_color_pieces(&self, color: Color) -> HashSet<Point> {
+    /// Coordinates of all pieces with a given color on the board,
+    /// in the order of [`Board::all_pieces`]
+    fn all_color_pieces(&self, color: Color) -> IndexSet<Point> {
         self.all_pieces()
             .into_iter()
             // by using `.unwrap()` instead of `.is_some_and()` I assure `.all_pieces()` works too,
@@ -271,8 +388,11 @@ impl Board {
             .collect()
     }
     /// Returns all the moves a player (`color`) can do.
-    pub fn all_moves(&self, color: Color) -> HashSet<Movement> {
-        let mut set = HashSet::new();
+    ///
+    /// They are ordered by the square of the piece (see [`Board::all_pieces`]),
+    /// then as in [`Board::filtered_move_set`], so they are the same on every run.
+    pub fn all_moves(&self, color: Color) -> IndexSet<Movement> {
+        let mut set = IndexSet::new();
         for coord in self.all_color_pieces(color) {
             if self[coord]
                 .as_ref()
@@ -283,16 +403,94 @@ impl Board {
         }
         set
     }
+    /// Whether a move returned by [`Board::filtered_move_set`]
+    /// doesn't leave the King of the player who does it in check.
+    ///
+    /// The move is done and taken back, so the board is the same after the call.
+    pub fn is_legal(&mut self, mov: &Movement) -> bool {
+        let color = self[mov.from].as_ref().unwrap().color();
+        let undo = self.make_move(mov);
+        let legal = self.check(color).is_none();
+        self.unmake_move(undo);
+        legal
+    }
+    /// Returns the moves `color` can play: the moves of [`Board::all_moves`]
+    /// which don't leave its King in check (pinned pieces and En Passant included).
+    ///
+    /// The board is cloned once to try the moves, see [`Board::is_legal`].
+    pub fn legal_moves(&self, color: Color) -> IndexSet<Movement> {
+        let mut board = self.clone();
+        self.all_moves(color)
+            .into_iter()
+            .filter(|mov| board.is_legal(mov))
+            .collect()
+    }
+    /// Legal moves of `color`, with a move for each piece a pawn can promote to.
+    pub(crate) fn legal_choices(&mut self, color: Color) -> Vec<(Movement, Option<char>)> {
+        let mut moves: Vec<_> = self.all_moves(color).into_iter().collect();
+        moves.retain(|mov| self.is_legal(mov));
+        moves
+            .into_iter()
+            .flat_map(|mov| {
+                let promotions = if self.is_promoting(&mov) {
+                    PROMOTIONS.map(Some).to_vec()
+                } else {
+                    vec![None]
+                };
+                promotions
+                    .into_iter()
+                    .map(move |promotion| (mov.clone(), promotion))
+            })
+            .collect()
+    }
+    /// Square of the piece eaten by a move, if it eats one
+    /// (not [`Movement::to`] for En Passant).
+    pub fn captured_square(&self, mov: &Movement) -> Option<Point> {
+        let color = self[mov.from].as_ref()?.color();
+        match mov.special {
+            Some(SpecialMove::PawnEat) if self[mov.to].is_none() => {
+                Some(mov.to - Point::new(0, color.sign()))
+            }
+            // in Chess960 the King can castle onto its own Rook
+            Some(SpecialMove::ShortCastle | SpecialMove::LongCastle) => None,
+            _ => Some(mov.to),
+        }
+        .filter(|pos| self[*pos].is_some())
+    }
     #[inline]
     pub fn is_promoting(&self, mov: &Movement) -> bool {
         self[mov.from].as_ref().is_some_and(|piece| {
             piece.as_any().is::<Pawn>() && piece.color().opposite().first_row() as isize == mov.to.y
         })
     }
+    /// Position of the Rook `color` still has the right to castle with
+    /// on the side of `castle` ([`SpecialMove::ShortCastle`] or [`SpecialMove::LongCastle`]):
+    /// an unmoved Rook on the first row, right (short) or left (long) of the unmoved King.
+    ///
+    /// This is only the castling *right*: the squares between the King and the Rook
+    /// may still be occupied or attacked.
+    pub fn castling_rook(&self, color: Color, castle: &SpecialMove) -> Option<Point> {
+        let row = color.first_row() as isize;
+        let unmoved = |x: isize, symbol: char| {
+            self[Point::new(x, row)].as_ref().is_some_and(|piece| {
+                piece.color() == color
+                    && piece.symbol() == symbol
+                    && piece.is_state(PieceState::NotYet.into())
+            })
+        };
+        let king = (0..Board::SIZE as isize).find(|x| unmoved(*x, 'K'))?;
+        let mut files = match castle {
+            SpecialMove::ShortCastle => king + 1..Board::SIZE as isize,
+            SpecialMove::LongCastle => 0..king,
+            _ => return None,
+        };
+        files.find(|x| unmoved(*x, 'R')).map(|x| Point::new(x, row))
+    }
     /// Returns `Some` if the move is an eating move, none otherwise
     /// `Some` contains the points (`usize`) added to the score to the `Color` player.
     ///
-    /// Also change the state of all pawns already moved.
+    /// Also change the state of all pawns which had just done a double move,
+    /// so that at most one pawn (the one moved now) is [`PawnState::JustDouble`].
     ///
     /// `promoted` is the piece to which the pawn should promote
     /// input to the user to promote the pawn or not.
@@ -301,15 +499,48 @@ impl Board {
         mov: &Movement,
         promoted: Option<Box<dyn Piece>>,
     ) -> Option<(f64, Color)> {
-        let piece = self[mov.from].as_ref().unwrap();
+        let color = self[mov.from].as_ref().unwrap().color();
+
+        self.make_move_with(mov, promoted)
+            .captured
+            .map(|(_, piece)| (piece.score(), color))
+    }
+    /// Does a move in place like [`Board::do_move`], returning what is needed
+    /// to take it back with [`Board::unmake_move`].
+    ///
+    /// A promoting pawn becomes a Queen, see [`Board::make_move_promoting`].
+    #[inline]
+    pub fn make_move(&mut self, mov: &Movement) -> UndoInfo {
+        self.make_move_promoting(mov, Some('Q'))
+    }
+    /// [`Board::make_move`], with the letter (B/N/R/Q) of the piece a promoting pawn becomes.
+    ///
+    /// `promotion` is ignored if the move doesn't promote.
+    pub fn make_move_promoting(&mut self, mov: &Movement, promotion: Option<char>) -> UndoInfo {
+        let color = self[mov.from].as_ref().unwrap().color();
+        let promoted = promotion
+            .filter(|_| self.is_promoting(mov))
+            .and_then(|c| piece_from_char(c, color, mov.to));
+
+        self.make_move_with(mov, promoted)
+    }
+    fn make_move_with(&mut self, mov: &Movement, promoted: Option<Box<dyn Piece>>) -> UndoInfo {
+        let piece = self[mov.from].as_deref().unwrap();
         let color = piece.color();
+        let moved = piece.clone_box();
+        let promotion = promoted.as_deref().map(Piece::symbol);
+        let hash = self.hash;
 
-        // score
-        let res = self[mov.to].as_ref().map(|piece| (piece.score(), color));
+        let rook = mov
+            .special
+            .as_ref()
+            .and_then(|castle| self.castling_rook(color, castle))
+            .and_then(|pos| self[pos].as_deref())
+            .map(Piece::clone_box);
 
-        // update pawns
-        let set: HashSet<_> = self
-            .all_color_pieces(self[mov.from].as_ref().unwrap().color())
+        let captured_pos = self.captured_square(mov);
+        let just_double: Vec<_> = self
+            .all_pieces()
             .into_iter()
             .filter(|coord| {
                 self[*coord]
@@ -317,56 +548,209 @@ impl Board {
                     .is_some_and(|piece| piece.is_state(PawnState::JustDouble.into()))
             })
             .collect();
-        for coord in set {
-            self[coord]
-                .as_mut()
-                .unwrap()
-                .set_state(PawnState::Already.into());
+
+        // remove from the hash what the move changes
+        self.hash ^= self.castling_hash() ^ zobrist::piece(&*moved, mov.from);
+        for coord in &just_double {
+            self.hash ^= self.en_passant_hash(*coord);
+        }
+        if let Some(rook) = &rook {
+            self.hash ^= zobrist::piece(&**rook, rook.pos());
+        }
+        let captured = captured_pos.map(|pos| (pos, self.set_square(pos, None).unwrap()));
+        if let Some((pos, piece)) = &captured {
+            self.hash ^= zobrist::piece(&**piece, *pos);
+        }
+
+        // update pawns: only the pawn double-pushed by this move can suffer an En Passant
+        for coord in &just_double {
+            if let Some(pawn) = self.square_mut(*coord).as_mut() {
+                pawn.set_state(PawnState::Already.into());
+            }
         }
 
         // move
         self.apply_move(mov, promoted);
 
-        res
+        // add to the hash what the move changed
+        self.hash ^=
+            self.castling_hash() ^ zobrist::piece(&**self[mov.to].as_ref().unwrap(), mov.to);
+        if mov.special == Some(SpecialMove::DoublePawn) {
+            self.hash ^= self.en_passant_hash(mov.to);
+        }
+        if rook.is_some() {
+            let pos = Self::castled_rook_pos(mov);
+            self.hash ^= zobrist::piece(&**self[pos].as_ref().unwrap(), pos);
+        }
+
+        UndoInfo {
+            movement: mov.clone(),
+            promotion,
+            captured,
+            moved,
+            rook,
+            just_double,
+            hash,
+        }
+    }
+    /// Takes back the last move done with [`Board::make_move`],
+    /// restoring the eaten piece, the promoted pawn, the states of the pieces and the hash.
+    pub fn unmake_move(&mut self, undo: UndoInfo) {
+        let UndoInfo {
+            movement: mov,
+            captured,
+            moved,
+            rook,
+            just_double,
+            hash,
+            ..
+        } = undo;
+
+        // in Chess960 the King and the Rook may swap squares, so both are lifted first
+        self.set_square(mov.to, None);
+        if rook.is_some() {
+            self.set_square(Self::castled_rook_pos(&mov), None);
+        }
+        self.set_square(mov.from, Some(moved));
+        if let Some(rook) = rook {
+            self.set_square(rook.pos(), Some(rook));
+        }
+        if let Some((pos, piece)) = captured {
+            self.set_square(pos, Some(piece));
+        }
+        for coord in just_double {
+            self.square_mut(coord)
+                .as_mut()
+                .unwrap()
+                .set_state(PawnState::JustDouble.into());
+        }
+        self.hash = hash;
+    }
+    /// Where the Rook goes with a castle.
+    #[inline]
+    fn castled_rook_pos(castle: &Movement) -> Point {
+        match castle.special {
+            Some(SpecialMove::ShortCastle) => castle.to - Point::new(1, 0),
+            _ => castle.to + Point::new(1, 0),
+        }
+    }
+    /// [Zobrist hash](zobrist) of the position, made of the pieces on the board,
+    /// the castling rights and the file of the pawn which can suffer an En Passant.
+    ///
+    /// The side to move is not part of the hash, see [`Game::zobrist`](crate::game::Game::zobrist).
+    /// An En Passant counts only if a pawn of the other player is next to the pawn (as in Polyglot),
+    /// even if the capture would be illegal (see [`Board::position_key`]).
+    ///
+    /// > The hash is updated by [`Board::make_move`] and [`Board::do_move`]:
+    /// > after changing the board by its squares, [`Board::rehash`] must be called.
+    #[inline(always)]
+    pub const fn zobrist(&self) -> u64 {
+        self.hash
+    }
+    /// [Zobrist hash](Board::zobrist) which tells the positions apart exactly,
+    /// as the repetition rules require: the En Passant file counts only
+    /// if the capture is a legal move.
+    pub fn position_key(&self) -> u64 {
+        let Some(pawn) = self.all_pieces().into_iter().find(|pos| {
+            let piece = self[*pos].as_deref().unwrap();
+            piece.as_any().is::<Pawn>() && piece.is_state(PawnState::JustDouble.into())
+        }) else {
+            return self.hash;
+        };
+        let en_passant = self.en_passant_hash(pawn);
+        if en_passant == 0 {
+            return self.hash;
+        }
+
+        let color = self[pawn].as_ref().unwrap().color().opposite();
+        let legal = self
+            .legal_moves(color)
+            .iter()
+            .any(|mov| mov.to != pawn && self.captured_square(mov) == Some(pawn));
+        if legal {
+            self.hash
+        } else {
+            self.hash ^ en_passant
+        }
+    }
+    /// Computes again the [Zobrist hash](Board::zobrist) of the whole position,
+    /// and the squares occupied by the pieces.
+    pub fn rehash(&mut self) {
+        self.occupied = None;
+        self.occupied = Some(self.occupied());
+        self.hash = self
+            .all_pieces()
+            .into_iter()
+            .fold(self.castling_hash(), |hash, pos| {
+                let piece = self[pos].as_deref().unwrap();
+                let mut hash = hash ^ zobrist::piece(piece, pos);
+                if piece.as_any().is::<Pawn>() && piece.is_state(PawnState::JustDouble.into()) {
+                    hash ^= self.en_passant_hash(pos);
+                }
+                hash
+            });
+    }
+    /// Part of the hash given by the castling rights.
+    fn castling_hash(&self) -> u64 {
+        [Color::White, Color::Black]
+            .into_iter()
+            .flat_map(|color| {
+                [SpecialMove::ShortCastle, SpecialMove::LongCastle]
+                    .into_iter()
+                    .filter(move |castle| self.castling_rook(color, castle).is_some())
+                    .map(move |castle| zobrist::castling(color, &castle))
+            })
+            .fold(0, |hash, key| hash ^ key)
+    }
+    /// Part of the hash given by a pawn which has just done a double move:
+    /// its file, if a pawn of the other player is next to it.
+    fn en_passant_hash(&self, pawn: Point) -> u64 {
+        let color = self[pawn].as_ref().unwrap().color();
+        let capturable = [-1, 1].into_iter().any(|x| {
+            self.get(pawn + Point::new(x, 0))
+                .and_then(Option::as_deref)
+                .is_some_and(|piece| piece.as_any().is::<Pawn>() && piece.color() != color)
+        });
+        if capturable {
+            zobrist::en_passant(pawn.x)
+        } else {
+            0
+        }
     }
     /// Without checking errors, move a piece.
+    ///
+    /// The eaten piece (even by En Passant) must have already been removed.
     fn apply_move(&mut self, mov: &Movement, promoted: Option<Box<dyn Piece>>) {
-        let piece = self[mov.from].as_mut().unwrap();
-        if mov
-            .special
-            .as_ref()
-            .is_some_and(|sm| sm == &SpecialMove::DoublePawn)
-        {
-            piece.set_state(PawnState::JustDouble.into());
+        let piece = self.square_mut(mov.from).as_mut().unwrap();
+        if piece.as_any().is::<Pawn>() {
+            // a pawn which has been moved can't do a double move anymore
+            piece.set_state(if mov.special == Some(SpecialMove::DoublePawn) {
+                PawnState::JustDouble.into()
+            } else {
+                PawnState::Already.into()
+            });
         }
 
-        match mov.special {
-            Some(SpecialMove::ShortCastle) => {
-                let mut rook = self[mov.from + Point::new(3, 0)].take().unwrap();
-                let new_pos = mov.to - Point::new(1, 0);
-
-                rook.set_pos(new_pos);
-                self[new_pos] = Some(rook);
-            }
-            Some(SpecialMove::LongCastle) => {
-                let mut rook = self[mov.from - Point::new(4, 0)].take().unwrap();
-                let new_pos = mov.to + Point::new(1, 0);
-
-                rook.set_pos(new_pos);
-                self[new_pos] = Some(rook);
-            }
-            Some(SpecialMove::PawnEat) if self[mov.to].is_none() => {
+        let rook = match &mov.special {
+            Some(castle @ (SpecialMove::ShortCastle | SpecialMove::LongCastle)) => {
                 let color = self[mov.from].as_ref().unwrap().color();
-                let new_pos = mov.to - Point::new(0, color.sign());
-
-                self[new_pos].take();
+                let rook_pos = self.castling_rook(color, castle).unwrap();
+                self.set_square(rook_pos, None)
             }
-            _ => {}
+            _ => None,
+        };
+        let piece = self.set_square(mov.from, None).unwrap(); // .unwrap() to ensure it still exists.
+
+        // the Rook is placed after lifting the King, as in Chess960 it may land on its square
+        if let Some(mut rook) = rook {
+            let new_pos = Self::castled_rook_pos(mov);
+            rook.set_pos(new_pos);
+            self.set_square(new_pos, Some(rook));
         }
-        let piece = self[mov.from].take().unwrap(); // .unwrap() to ensure it still exists.
 
-        self[mov.to] = promoted.or(Some(piece));
-        self[mov.to].as_mut().unwrap().set_pos(mov.to);
+        let mut piece = promoted.unwrap_or(piece);
+        piece.set_pos(mov.to);
+        self.set_square(mov.to, Some(piece));
     }
     /// Returns the King's coordinates of the given color.
     fn find_king(&self, color: Color) -> Point {
@@ -382,22 +766,26 @@ impl Board {
         }
         unreachable!("There should be a King");
     }
-    /// `color` is the color of the king about to be captured
+    /// A move of the other player eating the King of `color`, if it is in check.
     pub fn check(&self, color: Color) -> Option<Movement> {
         let king_pos = self.find_king(color);
-        self.all_moves(color.opposite())
+        let from = self
+            .attackers_of(king_pos, color.opposite())
+            .into_iter()
+            .next()?;
+        self.filtered_move_set(from)
             .into_iter()
             .find(|mov| mov.to == king_pos)
     }
     /// Can the player block the Check moving a piece? Returns the Movements that stops the check
     ///
-    /// Returns an HashSet of all the moves with which the player can block the piece by
+    /// Returns an IndexSet of all the moves with which the player can block the piece by
     /// eating it or putting a piece between it and the king.
     ///
     /// Doesn't check for the king moving itself (TODO might be added?)
     ///
     /// `color` is the color of the king about to be captured
-    pub fn is_check_stoppable(&self, color: Color) -> HashSet<Movement> {
+    pub fn is_check_stoppable(&self, color: Color) -> IndexSet<Movement> {
         let mut stop_cells = HashSet::new();
         let check_move = self.check(color).unwrap();
         // Adding to stop_cells
@@ -422,26 +810,214 @@ impl Board {
     }
     /// `color` is the color of the king about to be captured
     pub fn checks_around(&self, color: Color) -> bool {
+        let mut board = self.clone();
         self.filtered_move_set(self.find_king(color))
             .into_iter()
-            .all(|mov| {
-                let mut new_board = self.clone();
-                new_board.do_move(&mov, None); // moving the king won't ever promote anything
-
-                new_board.check(color).is_some()
-            })
+            .all(|mov| !board.is_legal(&mov))
     }
-    /// `color` is the color of the king about to be captured
+    /// Whether the King of `color` is in check and no legal move can save it
+    /// (see [`Board::legal_moves`]).
     #[inline]
     pub fn checkmate(&self, color: Color) -> bool {
-        // Return is check, all around check, and if check is not stoppable
-        self.check(color).is_some()
-            && self.checks_around(color)
-            && self.is_check_stoppable(color).is_empty()
+        self.check(color).is_some() && self.legal_moves(color).is_empty()
+    }
+    /// Whether neither player can checkmate anymore, with any sequence of legal moves,
+    /// because of the pieces left on the board:
+    /// - King vs King
+    /// - King and Bishop or King and Knight vs King
+    /// - Kings and any number of Bishops, all on squares of the same color
+    /// - a wall of blocked pawns that neither King can get through,
+    ///   with only Bishops on squares of the same color which can't eat the pawns
+    pub fn insufficient_material(&self) -> bool {
+        let mut minors = Vec::new();
+        for pos in self.all_pieces() {
+            let piece = self[pos].as_ref().unwrap().as_any();
+            if piece.is::<Pawn>() {
+                return self.dead_pawn_wall();
+            } else if piece.is::<Bishop>() || piece.is::<Knight>() {
+                minors.push((pos, piece.is::<Bishop>()));
+            } else if !piece.is::<King>() {
+                // a Rook or a Queen can always mate
+                return false;
+            }
+        }
+
+        let square_color = |pos: Point| (pos.x + pos.y) % 2;
+        match minors.as_slice() {
+            [] | [_] => true,
+            [(first, _), ..] => minors
+                .iter()
+                .all(|(pos, bishop)| *bishop && square_color(*pos) == square_color(*first)),
+        }
+    }
+    /// Whether the pawns can never move again and no King can ever be checkmated,
+    /// with only Kings, pawns and Bishops on squares of the same color left:
+    /// - every pawn is blocked by another pawn and has nothing to eat
+    /// - no Bishop can eat a pawn or be eaten by one, so the enemy pawns stand on
+    ///   the squares of the other color
+    /// - no King can reach an enemy pawn which isn't defended by another pawn
+    /// - wherever a King can be checked by a Bishop, it has a free square next to it
+    ///   (of the other color) which the enemy King can't reach
+    ///
+    /// The squares the Kings can reach are found ignoring the Bishops and the other King,
+    /// so a position might be reported as alive when it is dead, but not the opposite.
+    fn dead_pawn_wall(&self) -> bool {
+        let square_color = |pos: Point| (pos.x + pos.y) % 2;
+        let mut pawns = Vec::new();
+        let mut bishops = Vec::new();
+        for pos in self.all_pieces() {
+            let piece = self[pos].as_ref().unwrap();
+            match piece.symbol() {
+                'K' => {}
+                'P' => pawns.push((pos, piece.color())),
+                'B' => bishops.push((pos, piece.color())),
+                _ => return false,
+            }
+        }
+        // the square color the Bishops are on, if any
+        let bishops_on = match bishops.as_slice() {
+            [] => None,
+            [(first, _), ..] => {
+                if bishops
+                    .iter()
+                    .any(|(pos, _)| square_color(*pos) != square_color(*first))
+                {
+                    return false;
+                }
+                Some(square_color(*first))
+            }
+        };
+        let has_bishops = |color: Color| bishops.iter().any(|(_, c)| *c == color);
+
+        let pawn_at = |pos: Point| pawns.iter().find(|(p, _)| *p == pos).map(|(_, c)| *c);
+        let attacked_by_pawns = |pos: Point, by: Color| {
+            [-1, 1]
+                .into_iter()
+                .any(|dx| pawn_at(pos - Point::new(dx, by.sign())) == Some(by))
+        };
+        for &(pos, color) in &pawns {
+            let blocked = pawn_at(pos + Point::new(0, color.sign())).is_some();
+            let can_eat = [-1, 1].into_iter().any(|dx| {
+                self.get(pos + Point::new(dx, color.sign()))
+                    .and_then(Option::as_ref)
+                    .is_some_and(|piece| piece.color() != color)
+            });
+            if !blocked
+                || can_eat
+                || self[pos]
+                    .as_ref()
+                    .unwrap()
+                    .is_state(PawnState::JustDouble.into())
+                || (bishops_on == Some(square_color(pos)) && has_bishops(color.opposite()))
+            {
+                return false;
+            }
+        }
+
+        // The squares a King can ever walk on, `None` if it can eat a pawn
+        let region = |color: Color| {
+            let start = self.find_king(color);
+            let mut region = HashSet::from([start]);
+            let mut stack = vec![start];
+            while let Some(pos) = stack.pop() {
+                for (offset, _) in Point::all_around(1) {
+                    let next = pos + offset;
+                    if !Board::in_bounds(next)
+                        || region.contains(&next)
+                        || attacked_by_pawns(next, color.opposite())
+                    {
+                        continue;
+                    }
+                    match pawn_at(next) {
+                        Some(owner) if owner != color => return None,
+                        Some(_) => {}
+                        None => {
+                            region.insert(next);
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+            Some(region)
+        };
+        let (Some(white), Some(black)) = (region(Color::White), region(Color::Black)) else {
+            return false;
+        };
+        let Some(bishops_on) = bishops_on else {
+            // nothing can ever give check
+            return true;
+        };
+
+        [
+            (Color::White, &white, &black),
+            (Color::Black, &black, &white),
+        ]
+        .into_iter()
+        .filter(|(color, ..)| has_bishops(color.opposite()))
+        .all(|(color, region, enemy)| {
+            let covered = |pos: Point| {
+                enemy.contains(&pos)
+                    || Point::all_around(1)
+                        .into_iter()
+                        .any(|(offset, _)| enemy.contains(&(pos + offset)))
+            };
+            region
+                .iter()
+                .filter(|pos| square_color(**pos) == bishops_on)
+                .all(|pos| {
+                    [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                        .into_iter()
+                        .map(|offset| *pos + Point::from(offset))
+                        .any(|next| {
+                            Board::in_bounds(next)
+                                && pawn_at(next).is_none()
+                                && !attacked_by_pawns(next, color.opposite())
+                                && !covered(next)
+                        })
+                })
+        })
+    }
+    /// Whether `color` can't checkmate the other player with any series of legal moves,
+    /// by looking at the material (as when the other player runs out of time):
+    /// - `color` has only its King
+    /// - `color` has a single Knight, and the other player only a King and Queens
+    ///   (any other piece could block its King in a corner)
+    /// - `color` has only Bishops on squares of the same color, and the other player
+    ///   has no pawns, no Knights and no Bishops on squares of the other color
+    pub fn insufficient_winning_material(&self, color: Color) -> bool {
+        let (mut knights, mut bishops, mut others) = (0, Vec::new(), Vec::new());
+        for pos in self.all_pieces() {
+            let piece = self[pos].as_ref().unwrap();
+            match piece.symbol() {
+                'K' => {}
+                symbol if piece.color() != color => others.push((symbol, pos)),
+                'N' => knights += 1,
+                'B' => bishops.push(pos),
+                // a Pawn, a Rook or a Queen can always mate
+                _ => return false,
+            }
+        }
+
+        let square_color = |pos: &Point| (pos.x + pos.y) % 2;
+        match (knights, bishops.as_slice()) {
+            (0, []) => true,
+            (1, []) => others.iter().all(|(symbol, _)| *symbol == 'Q'),
+            (0, [first, ..]) => {
+                bishops
+                    .iter()
+                    .all(|pos| square_color(pos) == square_color(first))
+                    && others.iter().all(|(symbol, pos)| match symbol {
+                        'P' | 'N' => false,
+                        'B' => square_color(pos) == square_color(first),
+                        _ => true,
+                    })
+            }
+            _ => false,
+        }
     }
+    /// Whether `color` isn't in check but has no legal moves.
     #[inline(always)]
     pub fn stalemate(&self, color: Color) -> bool {
-        // No moves available and not check
-        self.check(color).is_none() && self.all_moves(color).is_empty()
+        self.check(color).is_none() && self.legal_moves(color).is_empty()
     }
 }
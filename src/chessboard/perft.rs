@@ -0,0 +1,47 @@
+//! [Perft](https://www.chessprogramming.org/Perft): counting the positions reachable
+//! with a number of moves, to compare the move generation with known results.
+
+use {
+    crate::{chessboard::Board, types::*},
+    indexmap::IndexMap,
+};
+
+impl Board {
+    /// Number of the different sequences of `depth` legal moves,
+    /// starting with a move of `color`.
+    ///
+    /// Every promotion counts as 4 moves, one for each piece.
+    pub fn perft(&mut self, color: Color, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        self.legal_choices(color)
+            .into_iter()
+            .map(|(mov, promotion)| {
+                if depth == 1 {
+                    return 1;
+                }
+                let undo = self.make_move_promoting(&mov, promotion);
+                let nodes = self.perft(color.opposite(), depth - 1);
+                self.unmake_move(undo);
+                nodes
+            })
+            .sum()
+    }
+    /// [`Board::perft`] of each legal move of `color`, written in coordinates
+    /// (see [`Movement::to_uci`]) and sorted.
+    pub fn divide(&mut self, color: Color, depth: usize) -> IndexMap<String, u64> {
+        let mut divide: IndexMap<_, _> = self
+            .legal_choices(color)
+            .into_iter()
+            .map(|(mov, promotion)| {
+                let undo = self.make_move_promoting(&mov, promotion);
+                let nodes = self.perft(color.opposite(), depth.saturating_sub(1));
+                self.unmake_move(undo);
+                (mov.to_uci(promotion), nodes)
+            })
+            .collect();
+        divide.sort_keys();
+        divide
+    }
+}
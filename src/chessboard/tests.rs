@@ -353,6 +353,78 @@ fn castle_blocked() {
     )
 }
 
+// Chess960 castles
+/// The board after castling with `castle`, checking that the move is taken back.
+fn castled(fen: &str, castle: SpecialMove) -> Board {
+    let mut board = Board::from_fen(fen).unwrap();
+    let before = board.clone();
+    let mov = board
+        .legal_moves(Color::White)
+        .into_iter()
+        .find(|mov| mov.special.as_ref() == Some(&castle))
+        .unwrap();
+    let undo = board.make_move(&mov);
+    let after = board.clone();
+    board.unmake_move(undo);
+    assert_eq!(board.fen_placement(), before.fen_placement());
+    assert_eq!(board.zobrist(), before.zobrist());
+    assert_eq!(board.fen_castling(), before.fen_castling());
+    after
+}
+#[test]
+fn chess960_castles() {
+    let symbol = |board: &Board, pos: &str| {
+        board[Point::try_from(pos).unwrap()]
+            .as_ref()
+            .map(|piece| piece.symbol())
+    };
+    // the King and the Rook swap squares
+    let fen = "rk5r/8/8/8/8/8/8/1R3KR1 w GB - 0 1";
+    let board = castled(fen, SpecialMove::ShortCastle);
+    assert_eq!(
+        (symbol(&board, "g1"), symbol(&board, "f1")),
+        (Some('K'), Some('R'))
+    );
+    assert_eq!(board.fen_castling(), "-");
+    // the King moves towards its Rook
+    let board = castled(fen, SpecialMove::LongCastle);
+    assert_eq!(
+        (symbol(&board, "c1"), symbol(&board, "d1")),
+        (Some('K'), Some('R'))
+    );
+    assert_eq!(symbol(&board, "b1"), None);
+    // the King is already on its square
+    let board = castled("4k3/8/8/8/8/8/8/6KR w K - 0 1", SpecialMove::ShortCastle);
+    assert_eq!(
+        (symbol(&board, "g1"), symbol(&board, "f1")),
+        (Some('K'), Some('R'))
+    );
+    assert_eq!(symbol(&board, "h1"), None);
+    // the King lands on the square of its Rook
+    let board = castled("4k3/8/8/8/8/8/8/2RK4 w Q - 0 1", SpecialMove::LongCastle);
+    assert_eq!(
+        (symbol(&board, "c1"), symbol(&board, "d1")),
+        (Some('K'), Some('R'))
+    );
+}
+#[test]
+fn chess960_castle_blocked() -> Result<(), Box<dyn std::error::Error>> {
+    let castles = |fen: &str| -> Result<usize, Box<dyn std::error::Error>> {
+        Ok(Board::from_fen(fen)?
+            .legal_moves(Color::White)
+            .into_iter()
+            .filter(Movement::is_castle)
+            .count())
+    };
+    // the square where the Rook lands is occupied
+    assert_eq!(castles("4k3/8/8/8/8/8/8/RK1N4 w A - 0 1")?, 0);
+    // the Rook doesn't move, the King goes through its square and lands on c1
+    assert_eq!(castles("1r2k3/8/8/8/8/8/8/3RK3 w D - 0 1")?, 1);
+    assert_eq!(castles("3rk3/8/8/8/8/8/8/3RK3 w D - 0 1")?, 0);
+    assert_eq!(castles("2r1k3/8/8/8/8/8/8/3RK3 w D - 0 1")?, 0);
+    Ok(())
+}
+
 // `.check()` tests
 #[test]
 fn rook_check() {
@@ -610,3 +682,389 @@ fn base() {
     assert!(!board.checkmate(Color::White));
     assert!(!board.checkmate(Color::Black));
 }
+
+// Regressions
+#[test]
+fn en_passant_only_pawns() {
+    let mut board = Board::empty();
+    let pawn_pos = Point::new(6, 3);
+    let queen_pos = Point::new(7, 3);
+    board[pawn_pos] = Some(Box::new(Pawn::new(Color::White, pawn_pos)));
+    board[queen_pos] = Some(Box::new(Queen::new(Color::Black, queen_pos)));
+
+    assert!(
+        board
+            .filtered_move_set(pawn_pos)
+            .into_iter()
+            .all(|mov| mov.to != Point::new(7, 4))
+    );
+}
+#[test]
+fn both_kings_castling() {
+    // The castles of a King must not need the castles of the other one
+    let mut board = Board::empty();
+    for color in [Color::White, Color::Black] {
+        let row = color.first_row() as isize;
+        let (king_pos, rook_pos) = (Point::new(4, row), Point::new(7, row));
+        board[king_pos] = Some(Box::new(King::new(color, king_pos)));
+        board[rook_pos] = Some(Box::new(Rook::new(color, rook_pos)));
+    }
+
+    assert!(board.check(Color::White).is_none());
+    assert!(
+        board
+            .filtered_move_set(Point::new(4, 0))
+            .into_iter()
+            .any(|mov| mov.special == Some(SpecialMove::ShortCastle))
+    );
+}
+
+// `.insufficient_material()` tests
+#[test]
+fn insufficient_material() -> Result<(), Box<dyn std::error::Error>> {
+    let dead = [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",                // K vs K
+        "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",              // K+B vs K
+        "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",              // K+N vs K
+        "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",             // same colored bishops
+        "4k3/8/8/8/8/8/1B6/2B1K3 w - - 0 1",            // same colored bishops, same player
+        "4k3/8/8/p1p1p1p1/P1P1P1P1/8/8/4K3 w - - 0 1",  // pawn wall
+        "4k3/8/8/p1p1p1p1/P1P1P1P1/8/8/3BK3 w - - 0 1", // pawn wall, bishop on the other color
+    ];
+    for fen in dead {
+        assert!(Board::from_fen(fen)?.insufficient_material(), "{fen}");
+    }
+
+    let alive = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",               // pawn
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",                // rook
+        "4kb2/8/8/8/8/8/8/3BK3 w - - 0 1",               // opposite colored bishops
+        "4kn2/8/8/8/8/8/8/2B1K3 w - - 0 1",              // bishop and knight
+        "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1",              // two knights
+        "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",              // bishop and knight, same player
+        "4k3/8/8/2p1p1p1/2P1P1P1/8/8/4K3 w - - 0 1",     // the King goes around the wall
+        "4k3/8/8/p1p1p1p1/P1P1P1P1/8/8/2B1K3 w - - 0 1", // the bishop eats a pawn
+        "4k3/8/8/p1ppp1p1/P1P1P1P1/8/8/4K3 w - - 0 1",   // cxd5
+        "4k3/8/8/p1p1p1p1/P1P1P1P1/8/8/3NK3 w - - 0 1",  // knight
+    ];
+    for fen in alive {
+        assert!(!Board::from_fen(fen)?.insufficient_material(), "{fen}");
+    }
+    Ok(())
+}
+
+// Zobrist hashing tests
+// `.make_move()` / `.unmake_move()` tests
+#[test]
+fn unmake_restores() -> Result<(), Box<dyn std::error::Error>> {
+    let fens = [
+        // castles, captures and En Passant (b4xa3)
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/Pp2P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq a3 0 1",
+        // promotions, with and without capture
+        "1r2k3/2P5/8/8/8/8/5p2/4K1N1 w - - 0 1",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen)?;
+        let before = format!("{board:?}");
+        for color in [Color::White, Color::Black] {
+            for mov in board.all_moves(color) {
+                let undo = board.make_move(&mov);
+                board.unmake_move(undo);
+                assert_eq!(format!("{board:?}"), before, "{mov:?}");
+            }
+        }
+    }
+    Ok(())
+}
+#[test]
+fn make_move_captures() -> Result<(), Box<dyn std::error::Error>> {
+    let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")?;
+    let mov = board
+        .filtered_move_set(Point::new(4, 4))
+        .into_iter()
+        .find(|mov| mov.special == Some(SpecialMove::PawnEat) && mov.to == Point::new(3, 5))
+        .unwrap();
+
+    let undo = board.make_move(&mov);
+    let (pos, piece) = undo.captured.as_ref().unwrap();
+    assert_eq!(*pos, Point::new(3, 4));
+    assert!(piece.as_any().is::<Pawn>());
+    assert!(board[Point::new(3, 4)].is_none());
+
+    board.unmake_move(undo);
+    assert!(
+        board[Point::new(3, 4)]
+            .as_ref()
+            .unwrap()
+            .is_state(PawnState::JustDouble.into())
+    );
+    Ok(())
+}
+#[test]
+fn make_move_promotes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut board = Board::from_fen("1r2k3/2P5/8/8/8/8/8/4K3 w - - 0 1")?;
+    let mov = board
+        .filtered_move_set(Point::new(2, 6))
+        .into_iter()
+        .find(|mov| mov.to == Point::new(1, 7))
+        .unwrap();
+
+    let undo = board.make_move_promoting(&mov, Some('N'));
+    assert_eq!(undo.promotion, Some('N'));
+    assert!(board[mov.to].as_ref().unwrap().as_any().is::<Knight>());
+
+    board.unmake_move(undo);
+    assert!(board[mov.from].as_ref().unwrap().as_any().is::<Pawn>());
+    assert!(board[mov.to].as_ref().unwrap().as_any().is::<Rook>());
+
+    let undo = board.make_move(&mov);
+    assert_eq!(undo.promotion, Some('Q'));
+    Ok(())
+}
+#[test]
+fn zobrist_incremental() -> Result<(), Box<dyn std::error::Error>> {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/Pp2P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq a3 0 1",
+        "r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq e3 0 1",
+        "1r2k3/2P5/8/8/8/8/5p2/4K1N1 w - - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        // captures of the Rooks losing the castling rights
+        "r3k2r/8/8/8/8/8/1B4b1/R3K2R w KQkq - 0 1",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen)?;
+        let hash = board.zobrist();
+        for color in [Color::White, Color::Black] {
+            for mov in board.all_moves(color) {
+                let undo = board.make_move_promoting(&mov, Some('N'));
+                let mut fresh = board.clone();
+                fresh.rehash();
+                assert_eq!(board.zobrist(), fresh.zobrist(), "{fen}: {mov:?}");
+                assert_ne!(board.zobrist(), hash, "{fen}: {mov:?}");
+
+                board.unmake_move(undo);
+                assert_eq!(board.zobrist(), hash);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn occupied_incremental() -> Result<(), Box<dyn std::error::Error>> {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/Pp2P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq a3 0 1",
+        "1r2k3/2P5/8/8/8/8/5p2/4K1N1 w - - 0 1",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen)?;
+        let occupied = board.occupied.unwrap();
+        for color in [Color::White, Color::Black] {
+            for mov in board.all_moves(color) {
+                let undo = board.make_move(&mov);
+                let mut fresh = board.clone();
+                fresh.rehash();
+                assert_eq!(board.occupied, fresh.occupied, "{fen}: {mov:?}");
+
+                board.unmake_move(undo);
+                assert_eq!(board.occupied, Some(occupied));
+            }
+        }
+    }
+
+    // changed by hand, the squares are found again
+    let mut board = Board::from_fen(fens[0])?;
+    let occupied = board.occupied();
+    board[Point::new(0, 0)] = None;
+    assert_eq!(board.occupied(), occupied & !1);
+    board.rehash();
+    assert_eq!(board.occupied, Some(occupied & !1));
+    Ok(())
+}
+#[test]
+fn zobrist_state() -> Result<(), Box<dyn std::error::Error>> {
+    let hash = |fen| Board::from_fen(fen).map(|board| board.zobrist());
+
+    assert_eq!(
+        hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")?,
+        Board::default().zobrist()
+    );
+    // castling rights
+    assert_ne!(
+        hash("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")?,
+        hash("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1")?
+    );
+    // En Passant only counts if a pawn can eat
+    assert_ne!(
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1")?,
+        hash("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1")?
+    );
+    assert_eq!(
+        hash("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1")?,
+        hash("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1")?
+    );
+    Ok(())
+}
+#[test]
+fn pawn_double_once() -> Result<(), Box<dyn std::error::Error>> {
+    let mut board = Board::default();
+    let mov = board
+        .filtered_move_set(Point::new(4, 1))
+        .into_iter()
+        .find(|mov| mov.special == Some(SpecialMove::CannotEat))
+        .unwrap();
+    board.do_move(&mov, None);
+
+    assert!(
+        board
+            .filtered_move_set(Point::new(4, 2))
+            .into_iter()
+            .all(|mov| mov.special != Some(SpecialMove::DoublePawn))
+    );
+    Ok(())
+}
+#[test]
+fn castle_only_with_own_rook() -> Result<(), Box<dyn std::error::Error>> {
+    // the black Knight took the Rook on h1
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1")?;
+    board[Point::new(7, 0)] = Some(Box::new(Knight::new(Color::Black, Point::new(7, 0))));
+
+    let castles: Vec<_> = board
+        .filtered_move_set(Point::new(4, 0))
+        .into_iter()
+        .filter_map(|mov| mov.special)
+        .collect();
+    assert_eq!(castles, [SpecialMove::LongCastle]);
+    Ok(())
+}
+#[test]
+fn no_long_castle_out_of_check() -> Result<(), Box<dyn std::error::Error>> {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K1r1 w Q - 0 1")?;
+    assert!(
+        board
+            .filtered_move_set(Point::new(4, 0))
+            .into_iter()
+            .all(|mov| mov.special.is_none())
+    );
+    Ok(())
+}
+
+// `.legal_moves()` tests
+/// Legal moves in coordinates (see [`Movement::to_uci`]), sorted
+fn legal_moves(fen: &str, color: Color) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let board = Board::from_fen(fen)?;
+    let mut moves: Vec<_> = board
+        .legal_moves(color)
+        .into_iter()
+        .map(|mov| mov.to_uci(None))
+        .collect();
+    moves.sort();
+    Ok(moves)
+}
+#[test]
+fn legal_moves_pinned() -> Result<(), Box<dyn std::error::Error>> {
+    // the white Bishop is pinned on the file, the white Rook on the diagonal
+    let moves = legal_moves("4r1k1/8/8/b7/8/8/3RB3/4K3 w - - 0 1", Color::White)?;
+    assert_eq!(moves, ["e1d1", "e1f1", "e1f2"]);
+    // a piece pinned along its own line can still move on it
+    let moves = legal_moves("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1", Color::White)?;
+    assert!(moves.contains(&"e2e8".to_string()));
+    assert!(!moves.contains(&"e2d2".to_string()));
+    Ok(())
+}
+#[test]
+fn legal_moves_in_check() -> Result<(), Box<dyn std::error::Error>> {
+    // only eating the Rook, blocking or moving the King away stop the check
+    let moves = legal_moves("4k3/8/8/8/8/1N6/8/r3K3 w - - 0 1", Color::White)?;
+    assert_eq!(moves, ["b3a1", "b3c1", "e1d2", "e1e2", "e1f2"]);
+    Ok(())
+}
+#[test]
+fn legal_moves_en_passant_discovered_check() -> Result<(), Box<dyn std::error::Error>> {
+    // bxc6 would leave both pawns' rank to the Rook
+    let moves = legal_moves("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", Color::White)?;
+    assert!(moves.contains(&"b5b6".to_string()));
+    assert!(!moves.contains(&"b5c6".to_string()));
+    // without the Rook it can be done
+    let moves = legal_moves("8/8/8/KPp5/8/8/8/7k w - c6 0 1", Color::White)?;
+    assert!(moves.contains(&"b5c6".to_string()));
+    Ok(())
+}
+
+// `.attackers_of()` / `.is_attacked()` tests
+#[test]
+fn attackers_of() -> Result<(), Box<dyn std::error::Error>> {
+    let board = Board::from_fen("4k3/8/8/3p4/2P1P3/1B6/8/R3K2R w KQ - 0 1")?;
+    let d5 = Point::try_from("d5")?;
+    let attackers = board.attackers_of(d5, Color::White);
+    let expected = ["c4", "e4"].map(|square| Point::try_from(square).unwrap());
+    assert_eq!(attackers, IndexSet::from(expected));
+
+    // the Bishop is blocked by the pawn on c4
+    assert!(!board.is_attacked(Point::try_from("e6")?, Color::White));
+    // pawn pushes are not attacks
+    assert!(!board.is_attacked(Point::try_from("d4")?, Color::Black));
+    assert!(board.is_attacked(Point::try_from("c4")?, Color::Black));
+    // the King attacks the squares around it
+    assert!(board.is_attacked(Point::try_from("d8")?, Color::Black));
+    Ok(())
+}
+#[test]
+fn check_by_pawn() -> Result<(), Box<dyn std::error::Error>> {
+    // a pawn in front of the King doesn't check it
+    let board = Board::from_fen("8/8/8/4p3/4K3/8/8/k7 w - - 0 1")?;
+    assert!(board.check(Color::White).is_none());
+
+    let board = Board::from_fen("8/8/8/3p4/4K3/8/8/k7 w - - 0 1")?;
+    let check = board.check(Color::White).unwrap();
+    assert_eq!(check.from, Point::try_from("d5")?);
+    assert_eq!(check.special, Some(SpecialMove::PawnEat));
+    Ok(())
+}
+// deterministic order tests
+#[test]
+fn pieces_in_square_order() -> Result<(), Box<dyn std::error::Error>> {
+    let board = Board::from_fen("4k3/8/8/3p4/2P1P3/1B6/8/R3K2R w KQ - 0 1")?;
+    let squares: Vec<_> = board
+        .all_pieces()
+        .into_iter()
+        .map(Point::algebraic)
+        .collect();
+    assert_eq!(squares, ["a1", "e1", "h1", "b3", "c4", "e4", "d5", "e8"]);
+    Ok(())
+}
+#[test]
+fn moves_in_the_same_order() -> Result<(), Box<dyn std::error::Error>> {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let uci = |board: &Board| -> Vec<String> {
+        board
+            .all_moves(Color::White)
+            .into_iter()
+            .map(|mov| mov.to_uci(None))
+            .collect()
+    };
+    let moves = uci(&Board::from_fen(fen)?);
+    // a new board, hashing the moves differently if they were in a HashSet
+    assert_eq!(uci(&Board::from_fen(fen)?), moves);
+
+    // the moves of a piece come before those of the pieces on the next squares
+    let from: Vec<_> = moves.iter().map(|mov| &mov[..2]).collect();
+    let mut squares: Vec<_> = from.clone();
+    squares.dedup();
+    let pieces: Vec<_> = Board::from_fen(fen)?
+        .all_pieces()
+        .into_iter()
+        .map(Point::algebraic)
+        .collect();
+    assert!(
+        squares
+            .iter()
+            .all(|square| pieces.contains(&square.to_string()))
+    );
+    assert!(squares.windows(2).all(|pair| {
+        let index = |square: &str| pieces.iter().position(|piece| piece == square);
+        index(pair[0]) < index(pair[1])
+    }));
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! [Zobrist hashing](https://www.chessprogramming.org/Zobrist_Hashing) of positions:
+//! a random key for each piece on each square, castling right and En Passant file,
+//! XORed together into a 64-bit hash which can be updated move by move.
+
+use crate::{chessboard::Board, geomath::Point, types::*};
+
+const SQUARES: usize = Board::SIZE * Board::SIZE;
+/// Pawn, Knight, Bishop, Rook, Queen and King
+const KINDS: usize = 6;
+
+const CASTLING: usize = 2 * KINDS * SQUARES;
+const EN_PASSANT: usize = CASTLING + 4;
+const SIDE: usize = EN_PASSANT + Board::SIZE;
+
+/// Random keys generated at compile time, so hashes are the same on every run.
+static KEYS: [u64; SIDE + 1] = keys();
+
+/// [SplitMix64](https://prng.di.unimi.it/splitmix64.c) sequence from a fixed seed.
+const fn keys() -> [u64; SIDE + 1] {
+    let mut keys = [0; SIDE + 1];
+    let mut state: u64 = 0x5EED_C0DE_CAFE_F00D;
+    let mut i = 0;
+    while i < keys.len() {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+/// Key of a piece on a square.
+pub fn piece(piece: &dyn Piece, pos: Point) -> u64 {
+    let kind = match piece.symbol() {
+        'P' => 0,
+        'N' => 1,
+        'B' => 2,
+        'R' => 3,
+        'Q' => 4,
+        _ => 5,
+    };
+    let color = usize::from(!bool::from(piece.color()));
+    let square = pos.y as usize * Board::SIZE + pos.x as usize;
+
+    KEYS[(color * KINDS + kind) * SQUARES + square]
+}
+
+/// Key of a castling right of `color`
+/// ([`SpecialMove::ShortCastle`] or [`SpecialMove::LongCastle`]).
+pub fn castling(color: Color, castle: &SpecialMove) -> u64 {
+    let side = usize::from(castle == &SpecialMove::LongCastle);
+    KEYS[CASTLING + usize::from(!bool::from(color)) * 2 + side]
+}
+
+/// Key of the file where an En Passant capture is possible.
+#[inline]
+pub fn en_passant(file: isize) -> u64 {
+    KEYS[EN_PASSANT + file as usize]
+}
+
+/// Key XORed into the hash when Black is to move.
+#[inline]
+pub fn black_to_move() -> u64 {
+    KEYS[SIDE]
+}
@@ -0,0 +1,363 @@
+//! Evaluation of positions, in centipawns (100 = a pawn).
+//!
+//! Besides the [`material`], [`evaluate`] scores where the pieces stand
+//! ([piece-square tables](https://www.chessprogramming.org/Piece-Square_Tables)),
+//! the pawn structure, the safety of the King, the mobility of the pieces and the bishop pair,
+//! each term with a middlegame and an endgame weight mixed by the [`phase`] of the game
+//! ([tapered eval](https://www.chessprogramming.org/Tapered_Eval)).
+
+use {
+    crate::{
+        chessboard::{BitBoard, Board},
+        geomath::Point,
+        types::*,
+    },
+    std::ops::{Add, AddAssign, Mul, Sub},
+};
+
+/// [`phase`] of a game with all its pieces
+pub const OPENING_PHASE: i32 = 24;
+
+/// Bonus of a piece on a square, as seen by White (the 8th row is the first one).
+type Table = [[i32; Board::SIZE]; Board::SIZE];
+
+#[rustfmt::skip]
+const PAWN_MIDDLEGAME: Table = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [ 50,  50,  50,  50,  50,  50,  50,  50],
+    [ 10,  10,  20,  30,  30,  20,  10,  10],
+    [  5,   5,  10,  25,  25,  10,   5,   5],
+    [  0,   0,   0,  20,  20,   0,   0,   0],
+    [  5,  -5, -10,   0,   0, -10,  -5,   5],
+    [  5,  10,  10, -20, -20,  10,  10,   5],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+];
+#[rustfmt::skip]
+const PAWN_ENDGAME: Table = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [ 40,  40,  40,  40,  40,  40,  40,  40],
+    [ 25,  25,  25,  25,  25,  25,  25,  25],
+    [ 15,  15,  15,  15,  15,  15,  15,  15],
+    [  8,   8,   8,   8,   8,   8,   8,   8],
+    [  4,   4,   4,   4,   4,   4,   4,   4],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+];
+#[rustfmt::skip]
+const KNIGHT: Table = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20,   0,   0,   0,   0, -20, -40],
+    [-30,   0,  10,  15,  15,  10,   0, -30],
+    [-30,   5,  15,  20,  20,  15,   5, -30],
+    [-30,   0,  15,  20,  20,  15,   0, -30],
+    [-30,   5,  10,  15,  15,  10,   5, -30],
+    [-40, -20,   0,   5,   5,   0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+];
+#[rustfmt::skip]
+const BISHOP: Table = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-10,   0,   5,  10,  10,   5,   0, -10],
+    [-10,   5,   5,  10,  10,   5,   5, -10],
+    [-10,   0,  10,  10,  10,  10,   0, -10],
+    [-10,  10,  10,  10,  10,  10,  10, -10],
+    [-10,   5,   0,   0,   0,   0,   5, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+];
+#[rustfmt::skip]
+const ROOK: Table = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [  5,  10,  10,  10,  10,  10,  10,   5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [  0,   0,   0,   5,   5,   0,   0,   0],
+];
+#[rustfmt::skip]
+const QUEEN: Table = [
+    [-20, -10, -10,  -5,  -5, -10, -10, -20],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-10,   0,   5,   5,   5,   5,   0, -10],
+    [ -5,   0,   5,   5,   5,   5,   0,  -5],
+    [ -5,   0,   5,   5,   5,   5,   0,  -5],
+    [-10,   0,   5,   5,   5,   5,   0, -10],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-20, -10, -10,  -5,  -5, -10, -10, -20],
+];
+/// The King hides behind its pawns while the Queens and the Rooks can attack it
+#[rustfmt::skip]
+const KING_MIDDLEGAME: Table = [
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [ 20,  20,   0,   0,   0,   0,  20,  20],
+    [ 20,  30,  10,   0,   0,  10,  30,  20],
+];
+/// and comes to the center once they are gone
+#[rustfmt::skip]
+const KING_ENDGAME: Table = [
+    [-50, -40, -30, -20, -20, -30, -40, -50],
+    [-30, -20, -10,   0,   0, -10, -20, -30],
+    [-30, -10,  20,  30,  30,  20, -10, -30],
+    [-30, -10,  30,  40,  40,  30, -10, -30],
+    [-30, -10,  30,  40,  40,  30, -10, -30],
+    [-30, -10,  20,  30,  30,  20, -10, -30],
+    [-30, -30,   0,   0,   0,   0, -30, -30],
+    [-50, -30, -30, -30, -30, -30, -30, -50],
+];
+
+/// For each pawn on a file after the first one of its color
+const DOUBLED_PAWN: Tapered = Tapered::new(-10, -20);
+/// For each pawn without pawns of its color on the files next to it
+const ISOLATED_PAWN: Tapered = Tapered::new(-10, -15);
+/// For a pawn without enemy pawns in front of it (on its file and the next ones),
+/// by its row counted from the side of its player
+const PASSED_PAWN: [Tapered; Board::SIZE] = [
+    Tapered::new(0, 0),
+    Tapered::new(5, 10),
+    Tapered::new(10, 20),
+    Tapered::new(15, 35),
+    Tapered::new(25, 60),
+    Tapered::new(40, 100),
+    Tapered::new(60, 150),
+    Tapered::new(0, 0),
+];
+const BISHOP_PAIR: Tapered = Tapered::new(30, 50);
+/// For each pawn of the King one row in front of it (on its file and the next ones)
+const PAWN_SHIELD: Tapered = Tapered::new(10, 0);
+/// For each of those files without a pawn of the King one or two rows in front of it
+const OPEN_KING_FILE: Tapered = Tapered::new(-15, 0);
+/// For each square next to the King attacked by the opponent
+const KING_ZONE_ATTACKED: Tapered = Tapered::new(-8, 0);
+
+/// A score in the middlegame and in the endgame, see [`phase`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+struct Tapered {
+    middlegame: i32,
+    endgame: i32,
+}
+
+impl Tapered {
+    #[inline]
+    const fn new(middlegame: i32, endgame: i32) -> Self {
+        Self {
+            middlegame,
+            endgame,
+        }
+    }
+    /// The score at a [`phase`] of the game.
+    #[inline]
+    const fn at(self, phase: i32) -> i32 {
+        (self.middlegame * phase + self.endgame * (OPENING_PHASE - phase)) / OPENING_PHASE
+    }
+}
+impl Add for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.middlegame + rhs.middlegame, self.endgame + rhs.endgame)
+    }
+}
+impl AddAssign for Tapered {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl Sub for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.middlegame - rhs.middlegame, self.endgame - rhs.endgame)
+    }
+}
+impl Mul<i32> for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: i32) -> Self {
+        Self::new(self.middlegame * rhs, self.endgame * rhs)
+    }
+}
+
+/// Value of a piece in centipawns, from [`Piece::score`] (Kings are worth nothing).
+#[inline]
+pub fn piece_value(piece: &dyn Piece) -> i32 {
+    if piece.as_any().is::<King>() {
+        0
+    } else {
+        (piece.score() * 100.) as i32
+    }
+}
+
+/// Material of `color` minus the material of the other player.
+pub fn material(board: &Board, color: Color) -> i32 {
+    board
+        .all_pieces()
+        .into_iter()
+        .map(|pos| {
+            let piece = board[pos].as_deref().unwrap();
+            let value = piece_value(piece);
+            if piece.color() == color {
+                value
+            } else {
+                -value
+            }
+        })
+        .sum()
+}
+
+/// How far the game is from the endgame, from [`OPENING_PHASE`] when all the pieces are
+/// on the board to 0 when only the Kings and the pawns are left.
+///
+/// Knights and Bishops count for 1, Rooks for 2 and Queens for 4
+/// (with promotions it is still at most [`OPENING_PHASE`]).
+pub fn phase(board: &Board) -> i32 {
+    pieces(board)
+        .map(|piece| match piece.symbol() {
+            'N' | 'B' => 1,
+            'R' => 2,
+            'Q' => 4,
+            _ => 0,
+        })
+        .sum::<i32>()
+        .min(OPENING_PHASE)
+}
+
+/// Score of the position for `color` minus its score for the other player.
+///
+/// It doesn't know whether the position is a checkmate or a draw, which is up to the search.
+pub fn evaluate(board: &Board, color: Color) -> i32 {
+    let bitboard = BitBoard::from(board);
+    let score = side(board, &bitboard, Color::White) - side(board, &bitboard, Color::Black);
+    score.at(phase(board)) * color.sign() as i32
+}
+
+/// The pieces on the board.
+#[inline]
+fn pieces(board: &Board) -> impl Iterator<Item = &dyn Piece> {
+    board
+        .iter()
+        .flatten()
+        .filter_map(|square| square.as_deref())
+}
+
+/// Score of `color` alone.
+fn side(board: &Board, bitboard: &BitBoard, color: Color) -> Tapered {
+    let mut score = Tapered::default();
+    let mut bishops = 0;
+    for piece in pieces(board).filter(|piece| piece.color() == color) {
+        let value = piece_value(piece);
+        score += Tapered::new(value, value);
+
+        // the tables are seen by White
+        let pos = piece.pos();
+        let (row, file) = match color {
+            Color::White => (Board::SIZE - 1 - pos.y as usize, pos.x as usize),
+            Color::Black => (pos.y as usize, pos.x as usize),
+        };
+        let (middlegame, endgame) = match piece.symbol() {
+            'P' => (&PAWN_MIDDLEGAME, &PAWN_ENDGAME),
+            'N' => (&KNIGHT, &KNIGHT),
+            'B' => (&BISHOP, &BISHOP),
+            'R' => (&ROOK, &ROOK),
+            'Q' => (&QUEEN, &QUEEN),
+            _ => (&KING_MIDDLEGAME, &KING_ENDGAME),
+        };
+        score += Tapered::new(middlegame[row][file], endgame[row][file]);
+
+        // moves above the usual number for the piece are a bonus, those below a malus
+        let (usual, weight) = match piece.symbol() {
+            'N' => (4, Tapered::new(4, 4)),
+            'B' => (7, Tapered::new(5, 5)),
+            'R' => (7, Tapered::new(2, 4)),
+            'Q' => (14, Tapered::new(1, 2)),
+            _ => continue,
+        };
+        let moves = bitboard.filtered_move_set(pos).len() as i32;
+        score += weight * (moves - usual);
+        if piece.symbol() == 'B' {
+            bishops += 1;
+        }
+    }
+    if bishops >= 2 {
+        score += BISHOP_PAIR;
+    }
+    score + pawn_structure(board, color) + king_safety(board, bitboard, color)
+}
+
+/// Doubled, isolated and passed pawns of `color`.
+fn pawn_structure(board: &Board, color: Color) -> Tapered {
+    let pawns = |color| -> Vec<Point> {
+        pieces(board)
+            .filter(|piece| piece.color() == color && piece.symbol() == 'P')
+            .map(|piece| piece.pos())
+            .collect()
+    };
+    let (own, enemy) = (pawns(color), pawns(color.opposite()));
+    let near = |a: &Point, b: &Point| (a.x - b.x).abs() <= 1;
+
+    let mut score = Tapered::default();
+    for pawn in &own {
+        if own
+            .iter()
+            .any(|other| other.x == pawn.x && other.y < pawn.y)
+        {
+            score += DOUBLED_PAWN;
+        }
+        if !own.iter().any(|other| (other.x - pawn.x).abs() == 1) {
+            score += ISOLATED_PAWN;
+        }
+        let ahead = |other: &Point| (other.y - pawn.y) * color.sign() > 0;
+        if !enemy.iter().any(|other| near(other, pawn) && ahead(other)) {
+            let row = (pawn.y - color.first_row() as isize).unsigned_abs();
+            score += PASSED_PAWN[row];
+        }
+    }
+    score
+}
+
+/// Pawns in front of the King of `color` and attacks on the squares around it.
+fn king_safety(board: &Board, bitboard: &BitBoard, color: Color) -> Tapered {
+    let Some(king) = pieces(board).find(|piece| piece.color() == color && piece.symbol() == 'K')
+    else {
+        return Tapered::default();
+    };
+    let king = king.pos();
+    let has_pawn = |pos: Point| {
+        Board::in_bounds(pos)
+            && board[pos]
+                .as_deref()
+                .is_some_and(|piece| piece.color() == color && piece.symbol() == 'P')
+    };
+
+    let mut score = Tapered::default();
+    for x in king.x - 1..=king.x + 1 {
+        if !(0..Board::SIZE as isize).contains(&x) {
+            continue;
+        }
+        let front = |rows: isize| Point::new(x, king.y + rows * color.sign());
+        if has_pawn(front(1)) {
+            score += PAWN_SHIELD;
+        } else if !has_pawn(front(2)) {
+            score += OPEN_KING_FILE;
+        }
+    }
+    for y in king.y - 1..=king.y + 1 {
+        for x in king.x - 1..=king.x + 1 {
+            let pos = Point::new(x, y);
+            if pos != king && Board::in_bounds(pos) && bitboard.is_attacked(pos, color.opposite()) {
+                score += KING_ZONE_ATTACKED;
+            }
+        }
+    }
+    score
+}
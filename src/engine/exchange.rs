@@ -0,0 +1,55 @@
+//! [Static exchange evaluation](https://www.chessprogramming.org/Static_Exchange_Evaluation):
+//! what a capture wins once both players have eaten on its square all they want.
+
+use {
+    super::piece_value,
+    crate::{
+        chessboard::{BitBoard, Board},
+        types::*,
+    },
+};
+
+/// Material won by the player doing `mov` (in centipawns, negative if it loses some)
+/// when both players then keep eating on [`Movement::to`],
+/// each time with their least valuable piece (see [`Piece::score`]), or stop when it doesn't pay.
+///
+/// Only the pieces attacking the square count, not pins, checks or promotions.
+/// A move which doesn't eat anything is worth 0 if the piece can't be eaten, less otherwise.
+pub fn see(board: &Board, mov: &Movement) -> i32 {
+    let Some(piece) = board[mov.from].as_deref() else {
+        return 0;
+    };
+    let mut bitboard = BitBoard::from(board);
+    bitboard.remove(mov.from);
+    let captured = board.captured_square(mov).map_or(0, |pos| {
+        bitboard.remove(pos);
+        piece_value(board[pos].as_deref().unwrap())
+    });
+
+    // what each capture wins if the exchange stopped after it
+    let mut gains = vec![captured];
+    let mut on_square = piece;
+    let mut color = piece.color().opposite();
+    while let Some(attacker) = bitboard
+        .attackers_of(mov.to, color)
+        .into_iter()
+        .filter_map(|pos| board[pos].as_deref())
+        .min_by(|a, b| a.score().total_cmp(&b.score()))
+    {
+        bitboard.remove(attacker.pos());
+        // the King can only eat a piece which isn't defended
+        if attacker.as_any().is::<King>() && bitboard.is_attacked(mov.to, color.opposite()) {
+            break;
+        }
+        gains.push(piece_value(on_square) - gains[gains.len() - 1]);
+        on_square = attacker;
+        color = color.opposite();
+    }
+
+    // each player only eats if it doesn't lose more than what it already has
+    gains
+        .into_iter()
+        .rev()
+        .reduce(|gain, previous| -(-previous).max(gain))
+        .unwrap()
+}
@@ -0,0 +1,488 @@
+//! A bot to play against: an [alpha-beta](https://www.chessprogramming.org/Alpha-Beta) search
+//! with iterative deepening over the legal moves, limited in depth and time,
+//! remembering the positions searched in a [`TranspositionTable`]
+//! and searching the most promising moves first (see [`MoveOrdering`]).
+//!
+//! At the end of the depth, the captures are searched until the position is quiet
+//! ([quiescence search](https://www.chessprogramming.org/Quiescence_Search)),
+//! so the last move searched isn't a piece eaten back right after.
+
+pub mod eval;
+pub mod exchange;
+pub mod ordering;
+#[cfg(test)]
+mod tests;
+pub mod transposition;
+
+pub use {eval::*, exchange::*, ordering::*, transposition::*};
+
+use {
+    crate::{
+        chessboard::{Board, UndoInfo, zobrist},
+        types::*,
+    },
+    std::{
+        cmp::Reverse,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Score of a checkmate given now, a mate in `n` plies is worth `MATE - n`.
+pub const MATE: i32 = 1_000_000;
+/// Scores above are mates, their distance is from the position and not the root in the table
+const MATE_BOUND: i32 = MATE / 2;
+/// Plies without captures or pawn moves after which the game is drawn (50-move rule)
+pub const FIFTY_MOVES: usize = 100;
+
+/// When a search has to stop
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Limits {
+    /// Maximum depth searched, in plies
+    pub depth: usize,
+    /// Maximum time to think.
+    ///
+    /// When it runs out, the depth being searched is dropped
+    /// and the best move of the previous one is played.
+    pub time: Option<Duration>,
+}
+impl Default for Limits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            time: Some(Duration::from_secs(5)),
+        }
+    }
+}
+
+/// The best move found by a search
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchResult {
+    pub movement: Movement,
+    /// Letter of the piece a pawn promotes to
+    pub promotion: Option<char>,
+    /// Score of the position for the player to move, in centipawns (see [`MATE`])
+    pub score: i32,
+    /// Depth of the last completed search
+    pub depth: usize,
+    /// Positions visited
+    pub nodes: u64,
+}
+
+/// Searches the best move of a position, see [`Engine::search`].
+#[derive(Debug)]
+pub struct Engine {
+    limits: Limits,
+    table: TranspositionTable,
+    ordering: MoveOrdering,
+    /// Set from another thread to stop the search
+    stop: Option<Arc<AtomicBool>>,
+    deadline: Option<Instant>,
+    /// Whether the search can stop before finishing the current depth
+    can_stop: bool,
+    stopped: bool,
+    nodes: u64,
+    /// Keys of the positions before the one searched, see [`Engine::set_history`]
+    history: Vec<u64>,
+    /// Plies since the last capture or pawn move of the position searched
+    halfmoves: usize,
+}
+
+impl Engine {
+    /// An engine with a table of [`DEFAULT_HASH`] megabytes.
+    #[inline]
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            table: TranspositionTable::default(),
+            ordering: MoveOrdering::default(),
+            stop: None,
+            deadline: None,
+            can_stop: false,
+            stopped: false,
+            nodes: 0,
+            history: Vec::new(),
+            halfmoves: 0,
+        }
+    }
+    /// Lets the search be stopped by setting `stop` to `true`,
+    /// like when the time runs out.
+    #[inline]
+    pub fn with_stop(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+    /// Uses a transposition table of `megabytes`, none if it is 0.
+    #[inline]
+    pub fn with_hash(mut self, megabytes: usize) -> Self {
+        self.set_hash(megabytes);
+        self
+    }
+    /// Replaces the transposition table by an empty one of `megabytes`, none if it is 0.
+    pub fn set_hash(&mut self, megabytes: usize) {
+        // the old table is freed before the new one is allocated
+        self.table = TranspositionTable::new(0);
+        self.table = TranspositionTable::new(megabytes);
+    }
+    /// Limits of the next searches.
+    #[inline]
+    pub const fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+    /// Positions of the game before the one to search (see [`Game::reversible_positions`]),
+    /// and the plies played since the last capture or pawn move.
+    ///
+    /// The search scores as a draw a position which repeats one of them (or one of its own),
+    /// or which comes after [`FIFTY_MOVES`] plies without captures or pawn moves.
+    ///
+    /// [`Game::reversible_positions`]: crate::game::Game::reversible_positions
+    #[inline]
+    pub fn set_history(&mut self, positions: Vec<u64>, halfmoves: usize) {
+        self.history = positions;
+        self.halfmoves = halfmoves;
+    }
+    /// The positions searched, kept from a search to the next.
+    #[inline]
+    pub const fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+    /// Forgets the positions searched, as when a new game starts.
+    #[inline]
+    pub fn clear_hash(&mut self) {
+        self.table.clear();
+        self.ordering = MoveOrdering::default();
+    }
+    /// Searches the best move of `color`, deeper and deeper until
+    /// the depth or the time of the [`Limits`] run out.
+    ///
+    /// The first depth is always completed, whatever the time limit.
+    ///
+    /// Returns `None` if `color` has no legal moves.
+    #[inline]
+    pub fn search(&mut self, board: &Board, color: Color) -> Option<SearchResult> {
+        self.search_with_info(board, color, |_| {})
+    }
+    /// Like [`Engine::search`], calling `info` with the best move of every completed depth.
+    pub fn search_with_info(
+        &mut self,
+        board: &Board,
+        color: Color,
+        mut info: impl FnMut(&SearchResult),
+    ) -> Option<SearchResult> {
+        let mut board = board.clone();
+        self.deadline = self.limits.time.map(|time| Instant::now() + time);
+        self.can_stop = false;
+        self.stopped = false;
+        self.nodes = 0;
+        self.table.new_search();
+        self.ordering.new_search();
+
+        let key = Self::key(&board, color);
+        let mut moves = board.legal_choices(color);
+        let hash_move = self
+            .table
+            .probe(key)
+            .and_then(|entry| entry.movement.as_ref());
+        self.ordering.sort(&board, &mut moves, hash_move, 0);
+        let mut result: Option<SearchResult> = None;
+        for depth in 1..=self.limits.depth.max(1) {
+            let mut best: Option<(usize, i32)> = None;
+            let mut alpha = -MATE - 1;
+            for (i, (mov, promotion)) in moves.iter().enumerate() {
+                let undo = self.play(&mut board, key, mov, *promotion);
+                let score = -self.alpha_beta(
+                    &mut board,
+                    color.opposite(),
+                    depth - 1,
+                    1,
+                    -MATE - 1,
+                    -alpha,
+                );
+                self.unplay(&mut board, undo);
+
+                if self.stopped {
+                    break;
+                }
+                if score > alpha {
+                    alpha = score;
+                    best = Some((i, score));
+                }
+            }
+            if self.stopped {
+                break;
+            }
+            let (i, score) = best?;
+
+            // the best move is searched first at the next depth
+            moves[..=i].rotate_right(1);
+            let (movement, promotion) = moves[0].clone();
+            let depth_result = SearchResult {
+                movement,
+                promotion,
+                score,
+                depth,
+                nodes: self.nodes,
+            };
+            self.table
+                .store(key, Some(moves[0].clone()), depth, score, Bound::Exact);
+            info(&depth_result);
+            result = Some(depth_result);
+            self.can_stop = true;
+            // a forced mate can't get any better
+            if score.abs() >= MATE - depth as i32 {
+                break;
+            }
+        }
+        result
+    }
+    /// Score of the position for `color` (fail-soft negamax),
+    /// searching `depth` more plies after the `ply` already played.
+    fn alpha_beta(
+        &mut self,
+        board: &mut Board,
+        color: Color,
+        depth: usize,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+        // draws depend on the moves played before, so they are not taken from the table
+        let key = Self::key(board, color);
+        if self.repeated(key) {
+            return 0;
+        }
+        if self.halfmoves >= FIFTY_MOVES {
+            // unless the last move gave checkmate
+            return if board.checkmate(color) {
+                ply - MATE
+            } else {
+                0
+            };
+        }
+        if depth == 0 {
+            return self.quiescence(board, color, ply, alpha, beta);
+        }
+
+        let entry = self.table.probe(key);
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            let score = Self::from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+        let hash_move = entry.and_then(|entry| entry.movement.clone());
+
+        let mut moves = board.legal_choices(color);
+        if moves.is_empty() {
+            // checkmate or stalemate
+            return if board.check(color).is_some() {
+                ply - MATE
+            } else {
+                0
+            };
+        }
+        self.ordering
+            .sort(board, &mut moves, hash_move.as_ref(), ply as usize);
+
+        let original_alpha = alpha;
+        let mut best = -MATE - 1;
+        let mut best_move = None;
+        for choice in moves {
+            let (mov, promotion) = &choice;
+            let undo = self.play(board, key, mov, *promotion);
+            let score =
+                -self.alpha_beta(board, color.opposite(), depth - 1, ply + 1, -beta, -alpha);
+            self.unplay(board, undo);
+
+            if self.stopped {
+                return 0;
+            }
+            if score > best {
+                best = score;
+                best_move = Some(choice);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                // the move is the best one, as it is the first above alpha
+                if let Some(choice) = &best_move {
+                    self.ordering.cutoff(board, choice, depth, ply as usize);
+                }
+                break;
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            // every move failed low, none of them is known to be the best
+            best_move = None;
+            Bound::Upper
+        };
+        self.table
+            .store(key, best_move, depth, Self::to_table(best, ply), bound);
+        best
+    }
+    /// Score of the position for `color` once the captures worth doing are played,
+    /// as it can also stand pat (play a quiet move) when none is good.
+    ///
+    /// The captures losing material (see [`see`]) are not searched, the others
+    /// and the promotions to Queen are searched from the one winning the most.
+    /// In check there's no standing pat: every move is searched,
+    /// and without any it is a checkmate `ply` plies after the root.
+    fn quiescence(
+        &mut self,
+        board: &mut Board,
+        color: Color,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+        let (mut best, moves) = if board.check(color).is_some() {
+            let evasions = board.legal_choices(color);
+            if evasions.is_empty() {
+                return ply - MATE;
+            }
+            (-MATE - 1, evasions)
+        } else {
+            let best = evaluate(board, color);
+            if best >= beta {
+                return best;
+            }
+            let mut captures: Vec<_> = board
+                .legal_choices(color)
+                .into_iter()
+                .filter(|(mov, promotion)| {
+                    (board.captured_square(mov).is_some() || board.is_promoting(mov))
+                        && matches!(promotion, None | Some('Q'))
+                })
+                .map(|choice| (see(board, &choice.0), choice))
+                // a pawn promoting is worth more than what it loses
+                .filter(|(exchange, (mov, _))| *exchange >= 0 || board.is_promoting(mov))
+                .collect();
+            captures.sort_by_key(|(exchange, _)| Reverse(*exchange));
+            (
+                best,
+                captures.into_iter().map(|(_, choice)| choice).collect(),
+            )
+        };
+        alpha = alpha.max(best);
+
+        for (mov, promotion) in moves {
+            let undo = board.make_move_promoting(&mov, promotion);
+            let score = -self.quiescence(board, color.opposite(), ply + 1, -beta, -alpha);
+            board.unmake_move(undo);
+
+            if self.stopped {
+                return 0;
+            }
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+    /// Plays a move of the search from the position of `key`,
+    /// keeping count of the positions for [`Engine::repeated`] and the 50-move rule.
+    ///
+    /// The move must be taken back with [`Engine::unplay`].
+    fn play(
+        &mut self,
+        board: &mut Board,
+        key: u64,
+        mov: &Movement,
+        promotion: Option<char>,
+    ) -> (UndoInfo, usize) {
+        let halfmoves = self.halfmoves;
+        let pawn = board[mov.from]
+            .as_ref()
+            .is_some_and(|piece| piece.as_any().is::<Pawn>());
+        self.halfmoves = if pawn || board.captured_square(mov).is_some() {
+            0
+        } else {
+            halfmoves + 1
+        };
+        self.history.push(key);
+        (board.make_move_promoting(mov, promotion), halfmoves)
+    }
+    /// Takes back a move of [`Engine::play`].
+    fn unplay(&mut self, board: &mut Board, (undo, halfmoves): (UndoInfo, usize)) {
+        board.unmake_move(undo);
+        self.history.pop();
+        self.halfmoves = halfmoves;
+    }
+    /// Whether the position of `key` has already occurred with the same player to move,
+    /// since the last capture or pawn move.
+    fn repeated(&self, key: u64) -> bool {
+        self.history
+            .iter()
+            .rev()
+            .take(self.halfmoves)
+            .skip(1)
+            .step_by(2)
+            .any(|position| *position == key)
+    }
+    /// Hash of the position in the table, with the player to move.
+    #[inline]
+    fn key(board: &Board, color: Color) -> u64 {
+        match color {
+            Color::White => board.zobrist(),
+            Color::Black => board.zobrist() ^ zobrist::black_to_move(),
+        }
+    }
+    /// Score of a position `ply` plies after the root, as it is stored in the table.
+    #[inline]
+    const fn to_table(score: i32, ply: i32) -> i32 {
+        if score >= MATE_BOUND {
+            score + ply
+        } else if score <= -MATE_BOUND {
+            score - ply
+        } else {
+            score
+        }
+    }
+    /// Inverse of [`Engine::to_table`].
+    #[inline]
+    const fn from_table(score: i32, ply: i32) -> i32 {
+        if score >= MATE_BOUND {
+            score - ply
+        } else if score <= -MATE_BOUND {
+            score + ply
+        } else {
+            score
+        }
+    }
+    /// Whether the search has to stop because the time ran out or it was stopped.
+    fn out_of_time(&mut self) -> bool {
+        if self.can_stop
+            && (self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+                || self
+                    .stop
+                    .as_ref()
+                    .is_some_and(|stop| stop.load(Ordering::Relaxed)))
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+}
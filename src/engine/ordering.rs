@@ -0,0 +1,154 @@
+//! [Move ordering](https://www.chessprogramming.org/Move_Ordering): the alpha-beta search
+//! prunes more when the best moves are searched first.
+//!
+//! The move of the [`TranspositionTable`](super::TranspositionTable) comes first,
+//! then the captures (see [`mvv_lva`]) and the promotions, then the quiet moves which were
+//! too good for the opponent elsewhere in the search: the killer moves of the same ply
+//! and the moves with the best history.
+
+use {
+    super::piece_value,
+    crate::{chessboard::Board, geomath::Point, types::*},
+    std::cmp::Reverse,
+};
+
+/// A move with the letter of the piece a pawn promotes to
+type Choice = (Movement, Option<char>);
+
+const SQUARES: usize = Board::SIZE * Board::SIZE;
+/// Killer moves kept for each ply
+const KILLERS: usize = 2;
+
+/// Priority of the captures and promotions, above every quiet move
+const CAPTURE: i32 = 2_000_000;
+/// Priority of the first killer move, above every history score
+const KILLER: i32 = 1_000_000;
+/// Largest history score, reached when the history is halved
+const MAX_HISTORY: i32 = KILLER / 2;
+
+/// Most Valuable Victim - Least Valuable Attacker: the value of the piece a move eats,
+/// minus a tenth of the value of the piece eating it (see [`Piece::score`]).
+///
+/// Capturing a Queen with a pawn comes before capturing it with a Rook,
+/// which comes before capturing a Rook with anything. A King eats first, as it can't be eaten back.
+/// The piece a pawn promotes to counts as eaten, minus the pawn.
+pub fn mvv_lva(board: &Board, mov: &Movement, promotion: Option<char>) -> i32 {
+    let Some(piece) = board[mov.from].as_deref() else {
+        return 0;
+    };
+    let victim = board
+        .captured_square(mov)
+        .and_then(|pos| board[pos].as_deref())
+        .map_or(0, piece_value);
+    let promoted = promotion
+        .and_then(|c| piece_from_char(c, piece.color(), mov.to))
+        .map_or(0, |promoted| piece_value(&*promoted) - piece_value(piece));
+    10 * (victim + promoted) - piece_value(piece)
+}
+
+/// Orders the moves of a search, learning from the cutoffs (see [`MoveOrdering::cutoff`]).
+#[derive(Clone, Debug)]
+pub struct MoveOrdering {
+    /// The last quiet moves which caused a cutoff at each ply
+    killers: Vec<[Option<Choice>; KILLERS]>,
+    /// How often a quiet move from a square to another one caused a cutoff,
+    /// weighted by the depth
+    history: Vec<[i32; SQUARES]>,
+}
+
+impl Default for MoveOrdering {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            killers: Vec::new(),
+            history: vec![[0; SQUARES]; SQUARES],
+        }
+    }
+}
+
+impl MoveOrdering {
+    /// Forgets the killer moves of the previous search and halves the history,
+    /// as the position changed.
+    pub fn new_search(&mut self) {
+        self.killers.clear();
+        self.age_history();
+    }
+    /// Priority of a move `ply` plies after the root, the highest is searched first.
+    pub fn priority(
+        &self,
+        board: &Board,
+        choice: &Choice,
+        hash_move: Option<&Choice>,
+        ply: usize,
+    ) -> i32 {
+        let (mov, promotion) = choice;
+        if hash_move == Some(choice) {
+            return i32::MAX;
+        }
+        if promotion.is_some() || board.captured_square(mov).is_some() {
+            return CAPTURE + mvv_lva(board, mov, *promotion);
+        }
+        if let Some(i) = self.killers.get(ply).and_then(|killers| {
+            killers
+                .iter()
+                .position(|killer| killer.as_ref() == Some(choice))
+        }) {
+            return KILLER - i as i32;
+        }
+        self.history(mov.from, mov.to)
+    }
+    /// Sorts the moves `ply` plies after the root, from the highest [`MoveOrdering::priority`].
+    ///
+    /// Moves with the same priority keep their order.
+    pub fn sort(
+        &self,
+        board: &Board,
+        moves: &mut [Choice],
+        hash_move: Option<&Choice>,
+        ply: usize,
+    ) {
+        moves.sort_by_cached_key(|choice| Reverse(self.priority(board, choice, hash_move, ply)));
+    }
+    /// Remembers a move, `ply` plies after the root, which was too good for the opponent
+    /// with `depth` plies left to search.
+    ///
+    /// Captures and promotions are already searched early, only quiet moves are remembered.
+    pub fn cutoff(&mut self, board: &Board, choice: &Choice, depth: usize, ply: usize) {
+        let (mov, promotion) = choice;
+        if promotion.is_some() || board.captured_square(mov).is_some() {
+            return;
+        }
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, Default::default());
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0].as_ref() != Some(choice) {
+            killers.rotate_right(1);
+            killers[0] = Some(choice.clone());
+        }
+
+        let history = &mut self.history[Self::square(mov.from)][Self::square(mov.to)];
+        *history += (depth * depth) as i32;
+        if *history >= MAX_HISTORY {
+            self.age_history();
+        }
+    }
+    /// The killer moves `ply` plies after the root, the last one first.
+    pub fn killers(&self, ply: usize) -> impl Iterator<Item = &Choice> {
+        self.killers.get(ply).into_iter().flatten().flatten()
+    }
+    /// History score of the quiet moves from `from` to `to`.
+    #[inline]
+    pub fn history(&self, from: Point, to: Point) -> i32 {
+        self.history[Self::square(from)][Self::square(to)]
+    }
+    fn age_history(&mut self) {
+        for score in self.history.iter_mut().flatten() {
+            *score /= 2;
+        }
+    }
+    #[inline(always)]
+    const fn square(pos: Point) -> usize {
+        pos.y as usize * Board::SIZE + pos.x as usize
+    }
+}
@@ -0,0 +1,452 @@
+use {
+    crate::{
+        engine::{
+            Bound, FIFTY_MOVES, MATE, MoveOrdering, OPENING_PHASE, TranspositionTable, evaluate,
+            material, mvv_lva, phase, see,
+        },
+        prelude::*,
+    },
+    std::{
+        error::Error,
+        sync::{Arc, atomic::AtomicBool},
+        time::Duration,
+    },
+};
+
+fn search(fen: &str, depth: usize) -> Result<Option<SearchResult>, Box<dyn Error>> {
+    let game = Game::from_fen(fen)?;
+    let limits = Limits { depth, time: None };
+    Ok(Engine::new(limits).search(&game.board, game.turn))
+}
+
+#[test]
+fn material_balance() -> Result<(), Box<dyn Error>> {
+    let board = Board::default();
+    assert_eq!(material(&board, Color::White), 0);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK2R w - - 0 1")?;
+    assert_eq!(material(&board, Color::White), 1400);
+    assert_eq!(material(&board, Color::Black), -1400);
+    Ok(())
+}
+
+/// Evaluation of a position for White.
+fn eval(fen: &str) -> Result<i32, Box<dyn Error>> {
+    Ok(evaluate(&Board::from_fen(fen)?, Color::White))
+}
+
+/// The same position with the colors swapped and the board upside down.
+fn mirror(fen: &str) -> String {
+    let fields: Vec<_> = fen.split(' ').collect();
+    let swap = |s: &str| -> String {
+        s.chars()
+            .map(|c| match c {
+                c if c.is_ascii_uppercase() => c.to_ascii_lowercase(),
+                c => c.to_ascii_uppercase(),
+            })
+            .collect()
+    };
+    let rows: Vec<_> = fields[0].split('/').rev().collect();
+    let turn = if fields[1] == "w" { "b" } else { "w" };
+    format!("{} {turn} - - 0 1", swap(&rows.join("/")))
+}
+
+#[test]
+fn evaluation() -> Result<(), Box<dyn Error>> {
+    assert_eq!(evaluate(&Board::default(), Color::White), 0);
+    for fen in [
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+        "8/5pk1/6p1/3P4/8/6P1/5PK1/8 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen)?;
+        let white = evaluate(&board, Color::White);
+        assert_eq!(evaluate(&board, Color::Black), -white, "{fen}");
+        // the same position for the other player
+        assert_eq!(eval(&mirror(fen))?, -white, "{fen}");
+    }
+
+    // a passed pawn is worth more as it advances
+    assert!(eval("4k3/8/3P4/8/8/8/8/4K3 w - - 0 1")? > eval("4k3/8/8/8/8/3P4/8/4K3 w - - 0 1")?);
+    // doubled and isolated pawns are weak
+    assert!(eval("4k3/8/8/8/8/8/PP6/4K3 w - - 0 1")? > eval("4k3/8/8/8/8/P7/P7/4K3 w - - 0 1")?);
+    // the bishop pair
+    assert!(eval("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1")? > eval("4k3/8/8/8/8/8/8/2B1KN2 w - - 0 1")?);
+    // in the endgame the King goes to the center
+    assert!(eval("8/8/8/4k3/8/8/8/K7 w - - 0 1")? < 0);
+    // but it stays behind its pawns in the middlegame
+    assert!(
+        eval("r1bq1rk1/ppppbppp/2n2n2/4p3/4P3/2N2N2/PPPPBPPP/R1BQ1RK1 w - - 0 1")?
+            > eval("r1bq1rk1/ppppbppp/2n2n2/4p3/4P3/2N2N1P/PPPPBPP1/R1BQ1R1K w - - 0 1")?
+    );
+    // a Knight in the center moves more than one in the corner
+    assert!(eval("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1")? > eval("4k3/8/8/8/8/8/8/N3K3 w - - 0 1")?);
+    Ok(())
+}
+
+#[test]
+fn game_phase() -> Result<(), Box<dyn Error>> {
+    assert_eq!(phase(&Board::default()), OPENING_PHASE);
+    assert_eq!(
+        phase(&Board::from_fen("8/5pk1/6p1/3P4/8/6P1/5PK1/8 w - - 0 1")?),
+        0
+    );
+    assert_eq!(
+        phase(&Board::from_fen("3qk3/8/8/8/8/8/8/R3K1N1 w - - 0 1")?),
+        7
+    );
+    // promoted pieces don't count past the opening
+    assert_eq!(
+        phase(&Board::from_fen("QQQQkQQQ/8/8/8/8/8/8/QQQQKQQQ w - - 0 1")?),
+        OPENING_PHASE
+    );
+    Ok(())
+}
+
+#[test]
+fn mate_in_one() -> Result<(), Box<dyn Error>> {
+    // back rank mate
+    let result = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 4)?.unwrap();
+    assert_eq!(result.movement.to, Point::try_from("a8")?);
+    assert_eq!(result.score, MATE - 1);
+    // the quiescence search sees the King can't get out of check at depth 1,
+    // the search stops there
+    assert_eq!(result.depth, 1);
+    Ok(())
+}
+
+#[test]
+fn avoid_mate() -> Result<(), Box<dyn Error>> {
+    // Black must stop Ra8#
+    let result = search("6k1/5ppp/8/8/8/8/r7/R5K1 b - - 0 1", 2)?.unwrap();
+    assert!(result.score > -MATE + 10);
+    Ok(())
+}
+
+#[test]
+fn take_the_queen() -> Result<(), Box<dyn Error>> {
+    let result = search("4k3/8/8/3q4/8/2N5/8/4K3 w - - 0 1", 2)?.unwrap();
+    assert_eq!(result.movement.to, Point::try_from("d5")?);
+    // a Knight up, give or take where the pieces stand
+    assert!((result.score - 300).abs() < 100, "{}", result.score);
+    Ok(())
+}
+
+#[test]
+fn promote() -> Result<(), Box<dyn Error>> {
+    let result = search("8/P7/8/8/8/8/k7/4K3 w - - 0 1", 1)?.unwrap();
+    assert_eq!(result.movement.to, Point::try_from("a8")?);
+    assert_eq!(result.promotion, Some('Q'));
+    Ok(())
+}
+
+#[test]
+fn no_moves() -> Result<(), Box<dyn Error>> {
+    // stalemate
+    assert_eq!(search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3)?, None);
+    Ok(())
+}
+
+#[test]
+fn time_limit() -> Result<(), Box<dyn Error>> {
+    let limits = Limits {
+        depth: 10,
+        time: Some(Duration::ZERO),
+    };
+    // the first depth is always searched
+    let result = Engine::new(limits)
+        .search(&Board::default(), Color::White)
+        .unwrap();
+    assert_eq!(result.depth, 1);
+    Ok(())
+}
+
+#[test]
+fn stop_flag() {
+    let limits = Limits {
+        depth: 10,
+        time: None,
+    };
+    let stop = Arc::new(AtomicBool::new(true));
+    let mut depths = vec![];
+    let result = Engine::new(limits)
+        .with_stop(stop)
+        .search_with_info(&Board::default(), Color::White, |info| {
+            depths.push(info.depth)
+        })
+        .unwrap();
+    // stopped after the first depth
+    assert_eq!(result.depth, 1);
+    assert_eq!(depths, [1]);
+}
+
+#[test]
+fn transposition_table() -> Result<(), Box<dyn Error>> {
+    let mov = Movement::new(Point::try_from("e2")?, Point::try_from("e4")?, None, None);
+    let mut table = TranspositionTable::new(1);
+    let slots = table.capacity() as u64;
+    assert!(slots > 0);
+    assert_eq!(table.probe(7), None);
+
+    table.store(7, Some((mov.clone(), None)), 3, 50, Bound::Exact);
+    let entry = table.probe(7).unwrap();
+    assert_eq!(entry.movement, Some((mov.clone(), None)));
+    assert_eq!(
+        (entry.depth, entry.score, entry.bound),
+        (3, 50, Bound::Exact)
+    );
+
+    // another position in the same slot, searched less deep
+    table.store(7 + slots, None, 2, 0, Bound::Upper);
+    assert_eq!(table.probe(7 + slots), None);
+    assert!(table.probe(7).is_some());
+    // searched as deep
+    table.store(7 + slots, None, 3, 0, Bound::Upper);
+    assert_eq!(table.probe(7), None);
+    assert_eq!(table.probe(7 + slots).unwrap().bound, Bound::Upper);
+
+    // an entry of the previous search is replaced
+    table.new_search();
+    table.store(7, None, 1, -20, Bound::Lower);
+    assert_eq!(table.probe(7).unwrap().depth, 1);
+    // the best move is kept when none is found
+    table.store(7, Some((mov.clone(), None)), 1, 0, Bound::Exact);
+    table.store(7, None, 2, -20, Bound::Upper);
+    assert_eq!(table.probe(7).unwrap().movement, Some((mov, None)));
+
+    table.clear();
+    assert_eq!(table.probe(7), None);
+    // a table of 0 megabytes stores nothing
+    let mut table = TranspositionTable::new(0);
+    table.store(7, None, 1, 0, Bound::Exact);
+    assert_eq!(table.probe(7), None);
+    Ok(())
+}
+
+#[test]
+fn hash_table() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")?;
+    let limits = Limits {
+        depth: 3,
+        time: None,
+    };
+    let without = Engine::new(limits)
+        .with_hash(0)
+        .search(&game.board, game.turn)
+        .unwrap();
+    let mut engine = Engine::new(limits);
+    let with = engine.search(&game.board, game.turn).unwrap();
+    // the same score; within 3 plies no position is reached by two orders of the moves,
+    // so the table only gives the best moves of the previous depths, which the killers
+    // and the history mostly find anyway (see `hash_table_transpositions` for the nodes)
+    assert_eq!(with.score, without.score);
+
+    // the position is stored with its best move
+    let entry = engine.table().probe(game.zobrist()).unwrap();
+    assert_eq!(
+        entry.movement,
+        Some((with.movement.clone(), with.promotion))
+    );
+    assert_eq!((entry.depth, entry.bound), (3, Bound::Exact));
+    // a new search starts from the table
+    let again = engine.search(&game.board, game.turn).unwrap();
+    assert_eq!(again.score, with.score);
+    assert!(again.nodes < with.nodes);
+    Ok(())
+}
+
+#[test]
+fn hash_table_transpositions() -> Result<(), Box<dyn Error>> {
+    // the Kings reach the same squares in many orders, found in the table
+    let game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1")?;
+    let limits = Limits {
+        depth: 5,
+        time: None,
+    };
+    let without = Engine::new(limits)
+        .with_hash(0)
+        .search(&game.board, game.turn)
+        .unwrap();
+    let with = Engine::new(limits).search(&game.board, game.turn).unwrap();
+    assert_eq!(with.score, without.score);
+    assert!(with.nodes < without.nodes);
+    Ok(())
+}
+
+#[test]
+fn mate_through_table() -> Result<(), Box<dyn Error>> {
+    // the mate found in the first search is still a mate in one in the second one
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    let mut engine = Engine::new(Limits {
+        depth: 4,
+        time: None,
+    });
+    for _ in 0..2 {
+        let result = engine.search(&game.board, game.turn).unwrap();
+        assert_eq!(result.score, MATE - 1);
+        assert_eq!(result.movement.to, Point::try_from("a8")?);
+    }
+    Ok(())
+}
+
+#[test]
+fn repetition_draw() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1")?;
+    let limits = Limits {
+        depth: 2,
+        time: None,
+    };
+    let first = Engine::new(limits).search(&game.board, game.turn).unwrap();
+    assert!(first.score > 0);
+
+    // the position after the best move was played just before
+    let mut board = game.board.clone();
+    board.make_move_promoting(&first.movement, first.promotion);
+    let repeated = Engine::key(&board, Color::Black);
+    let mut engine = Engine::new(limits);
+    engine.set_history(vec![repeated], 1);
+    let second = engine.search(&game.board, game.turn).unwrap();
+    assert_ne!(second.movement, first.movement);
+    assert!(second.score > 0);
+
+    // a move without captures or pawn moves reaches the 50-move rule, as in the game
+    let mut game = Game::from_fen(&format!("4k3/8/8/8/8/8/8/Q3K3 w - - {} 1", FIFTY_MOVES - 1))?;
+    let mut engine = Engine::new(limits);
+    engine.set_history(game.reversible_positions(), game.move_count.into());
+    let last = engine.search(&game.board, game.turn).unwrap();
+    assert_eq!(last.score, 0);
+    game.play(&last.movement, last.promotion);
+    assert_eq!(game.end(), Some(Outcome::Draw));
+    Ok(())
+}
+
+#[test]
+fn static_exchange() -> Result<(), Box<dyn Error>> {
+    let exchange = |fen: &str, uci: &str| -> Result<i32, Box<dyn Error>> {
+        let game = Game::from_fen(fen)?;
+        let (mov, _) = Movement::from_uci(&game.board, game.turn, uci)?;
+        Ok(see(&game.board, &mov))
+    };
+    // a free pawn
+    assert_eq!(
+        exchange("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5")?,
+        100
+    );
+    // a pawn defended by a pawn
+    assert_eq!(
+        exchange("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", "d1d5")?,
+        -800
+    );
+    // the pieces behind the ones eating join the exchange
+    assert_eq!(
+        exchange(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        )?,
+        -200
+    );
+    // the King eats back only when the Rook doesn't defend
+    assert_eq!(exchange("8/8/4k3/3p4/2P5/8/8/4K3 w - - 0 1", "c4d5")?, 0);
+    assert_eq!(exchange("8/8/4k3/3p4/2P5/8/8/3RK3 w - - 0 1", "c4d5")?, 100);
+    // En Passant
+    assert_eq!(exchange("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6")?, 100);
+    // a quiet move to a square where the piece is lost
+    assert_eq!(exchange("4k3/8/2p5/8/8/8/8/3QK3 w - - 0 1", "d1d5")?, -900);
+    Ok(())
+}
+
+#[test]
+fn horizon() -> Result<(), Box<dyn Error>> {
+    // at depth 1 the pawn looks free, but it is eaten back
+    let result = search("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", 1)?.unwrap();
+    assert_ne!(result.movement.to, Point::try_from("d5")?);
+    assert!((result.score - 700).abs() < 100, "{}", result.score);
+    Ok(())
+}
+
+#[test]
+fn quiescence_checks() -> Result<(), Box<dyn Error>> {
+    // Rxd8 is a mate, seen by the quiescence search as Black has no way out of check
+    let result = search("3n3k/6pp/8/8/8/8/7K/q2R4 w - - 0 1", 1)?.unwrap();
+    assert_eq!(result.movement.to, Point::try_from("d8")?);
+    assert_eq!(result.score, MATE - 1);
+
+    // the pawn promotes once the depth is over
+    let result = search("4k3/8/8/8/8/8/p7/4K3 w - - 0 1", 1)?.unwrap();
+    assert!(result.score < -500, "{}", result.score);
+    Ok(())
+}
+
+#[test]
+fn move_ordering() -> Result<(), Box<dyn Error>> {
+    let mut board = Board::from_fen("4k3/8/8/3q4/2P5/8/4r3/K2R1Q2 w - - 0 1")?;
+    let uci = |choice: &(Movement, Option<char>)| choice.0.to_uci(choice.1);
+    let mut moves = board.legal_choices(Color::White);
+    let mut ordering = MoveOrdering::default();
+
+    // the Queen with the pawn, then with the Rook, then the Rook with the Queen
+    ordering.sort(&board, &mut moves, None, 0);
+    assert_eq!(
+        moves[..3].iter().map(uci).collect::<Vec<_>>(),
+        ["c4d5", "d1d5", "f1e2"]
+    );
+    let (pawn, rook) = (&moves[0].0, &moves[1].0);
+    assert_eq!(mvv_lva(&board, pawn, None), 8900);
+    assert_eq!(mvv_lva(&board, rook, None), 8500);
+
+    // the move of the table comes first
+    let quiet = moves
+        .iter()
+        .find(|choice| uci(choice) == "a1b1")
+        .unwrap()
+        .clone();
+    ordering.sort(&board, &mut moves, Some(&quiet), 0);
+    assert_eq!(moves[0], quiet);
+
+    // a quiet move which caused a cutoff comes right after the captures at the same ply
+    ordering.cutoff(&board, &quiet, 3, 2);
+    assert_eq!(ordering.killers(2).collect::<Vec<_>>(), [&quiet]);
+    assert_eq!(ordering.history(quiet.0.from, quiet.0.to), 9);
+    ordering.sort(&board, &mut moves, None, 2);
+    assert_eq!(moves[3], quiet);
+    ordering.sort(&board, &mut moves, None, 1);
+    assert_eq!(ordering.killers(1).count(), 0);
+
+    // captures are not remembered
+    let capture = moves
+        .iter()
+        .find(|choice| uci(choice) == "f1e2")
+        .unwrap()
+        .clone();
+    ordering.cutoff(&board, &capture, 3, 2);
+    assert_eq!(ordering.killers(2).count(), 1);
+    assert_eq!(ordering.history(capture.0.from, capture.0.to), 0);
+
+    // the last killer moves, the most recent first
+    for killer in ["d1d3", "d1d4"] {
+        let killer = moves.iter().find(|choice| uci(choice) == killer).unwrap();
+        ordering.cutoff(&board, killer, 1, 2);
+    }
+    let killers: Vec<_> = ordering.killers(2).map(uci).collect();
+    assert_eq!(killers, ["d1d4", "d1d3"]);
+
+    // a new search keeps half the history
+    ordering.new_search();
+    assert_eq!(ordering.killers(2).count(), 0);
+    assert_eq!(ordering.history(quiet.0.from, quiet.0.to), 4);
+    Ok(())
+}
+
+#[test]
+fn deterministic_search() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")?;
+    let limits = Limits {
+        depth: 3,
+        time: None,
+    };
+    // the same moves searched in the same order, on every run
+    let first = Engine::new(limits).search(&game.board, game.turn);
+    let second = Engine::new(limits).search(&game.board, game.turn);
+    assert_eq!(first, second);
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! [Transposition table](https://www.chessprogramming.org/Transposition_Table):
+//! the results of the positions already searched, found again through other move orders.
+
+use {
+    crate::types::Movement,
+    std::{fmt, mem},
+};
+
+/// Size of the table of an [`Engine`](super::Engine) when none is given, in megabytes
+pub const DEFAULT_HASH: usize = 16;
+/// Largest table the protocols let the GUI ask for, in megabytes
+pub const MAX_HASH: usize = 4096;
+
+/// What the score of an [`Entry`] is, as the search can stop before knowing the exact one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound {
+    /// The score of the position
+    Exact,
+    /// The position is worth at least the score (a move was too good for the opponent)
+    Lower,
+    /// The position is worth at most the score (no move was good enough)
+    Upper,
+}
+
+/// A position searched, see [`TranspositionTable::store`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    /// Hash of the position, with the player to move
+    pub key: u64,
+    /// Best move found, with the letter of the piece a pawn promotes to
+    pub movement: Option<(Movement, Option<char>)>,
+    /// Plies searched after the position
+    pub depth: usize,
+    pub score: i32,
+    pub bound: Bound,
+    /// Search which stored the entry, see [`TranspositionTable::new_search`]
+    pub age: u8,
+}
+
+/// A fixed number of [`Entry`], one for each slot the hashes are split into.
+///
+/// When two positions share a slot, the one searched deeper is kept,
+/// unless it was stored by an older search.
+#[derive(Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    age: u8,
+}
+
+impl TranspositionTable {
+    /// A table taking up to `megabytes` of memory, which stores nothing if it is 0.
+    pub fn new(megabytes: usize) -> Self {
+        let slots = megabytes * 1024 * 1024 / mem::size_of::<Option<Entry>>();
+        Self {
+            entries: vec![None; slots],
+            age: 0,
+        }
+    }
+    /// Number of entries the table can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+    /// Removes every entry.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+        self.age = 0;
+    }
+    /// Makes the entries of the previous searches older,
+    /// so they are replaced first.
+    #[inline]
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+    /// The entry of the position with the hash `key`, if it is still in the table.
+    pub fn probe(&self, key: u64) -> Option<&Entry> {
+        self.entries
+            .get(self.slot(key)?)?
+            .as_ref()
+            .filter(|entry| entry.key == key)
+    }
+    /// Stores the result of a search of the position with the hash `key`,
+    /// if its slot is empty, older or searched less deep.
+    pub fn store(
+        &mut self,
+        key: u64,
+        movement: Option<(Movement, Option<char>)>,
+        depth: usize,
+        score: i32,
+        bound: Bound,
+    ) {
+        let Some(slot) = self.slot(key) else {
+            return;
+        };
+        let age = self.age;
+        let entry = &mut self.entries[slot];
+        if entry
+            .as_ref()
+            .is_some_and(|entry| entry.age == age && entry.depth > depth)
+        {
+            return;
+        }
+        // the best move of a position is kept if the new search didn't find one
+        let movement = movement.or_else(|| {
+            entry
+                .take()
+                .filter(|entry| entry.key == key)
+                .and_then(|entry| entry.movement)
+        });
+        *entry = Some(Entry {
+            key,
+            movement,
+            depth,
+            score,
+            bound,
+            age,
+        });
+    }
+    #[inline(always)]
+    fn slot(&self, key: u64) -> Option<usize> {
+        (!self.entries.is_empty()).then(|| (key % self.entries.len() as u64) as usize)
+    }
+}
+impl Default for TranspositionTable {
+    #[inline]
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH)
+    }
+}
+impl fmt::Debug for TranspositionTable {
+    /// Doesn't write the entries, as there are so many.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("capacity", &self.capacity())
+            .field("age", &self.age)
+            .finish()
+    }
+}
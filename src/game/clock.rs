@@ -0,0 +1,285 @@
+//! Chess clocks: the time of each player runs down while they think about their move,
+//! and the player whose time runs out (whose flag falls) loses the game.
+
+use {
+    crate::types::Color,
+    std::{
+        error::Error,
+        fmt::{self, Display},
+        str::FromStr,
+        time::Duration,
+    },
+};
+
+/// A period of a [`TimeControl`]: `moves` moves to play in `time`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Stage {
+    /// Moves to play in the stage, `None` for the rest of the game (sudden death)
+    pub moves: Option<u32>,
+    /// Time added to the clock at the start of the stage
+    pub time: Duration,
+}
+
+/// Time given back to a player after each of their moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Bonus {
+    #[default]
+    None,
+    /// The increment is added after each move, even if the move took less time
+    Fischer(Duration),
+    /// The time the move took is given back, up to the delay
+    Bronstein(Duration),
+}
+
+/// How much time the players have for the game, e.g. `5+3`
+/// (5 minutes for the game and 3 seconds of increment per move).
+///
+/// It is written as its stages separated by `+`, each one as `<moves>/<minutes>` except the last
+/// one which is the rest of the game (`<minutes>`), followed by the [`Bonus`] in seconds:
+/// `+<seconds>` for a Fischer increment or `d<seconds>` for a Bronstein delay.
+///
+/// | Time control | Meaning |
+/// |---|---|
+/// | `15` | 15 minutes for the game (sudden death) |
+/// | `3+2` | 3 minutes for the game, 2 seconds added after each move |
+/// | `5d3` | 5 minutes for the game, up to 3 seconds given back after each move |
+/// | `40/90+30` | 90 minutes for the first 40 moves, then 30 minutes for the rest of the game |
+/// | `40/90+30+30` | the same, with 30 seconds added after each move |
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TimeControl {
+    /// Stages of the game, only the last one has no number of moves
+    pub stages: Vec<Stage>,
+    pub bonus: Bonus,
+}
+
+impl TimeControl {
+    /// `minutes` for the whole game.
+    #[inline]
+    pub fn sudden_death(minutes: u64) -> Self {
+        Self {
+            stages: vec![Stage {
+                moves: None,
+                time: Duration::from_secs(minutes * 60),
+            }],
+            bonus: Bonus::None,
+        }
+    }
+    /// `minutes` for the whole game, `increment` seconds added after each move.
+    #[inline]
+    pub fn fischer(minutes: u64, increment: u64) -> Self {
+        Self {
+            bonus: Bonus::Fischer(Duration::from_secs(increment)),
+            ..Self::sudden_death(minutes)
+        }
+    }
+    /// `minutes` for the whole game, up to `delay` seconds given back after each move.
+    #[inline]
+    pub fn bronstein(minutes: u64, delay: u64) -> Self {
+        Self {
+            bonus: Bonus::Bronstein(Duration::from_secs(delay)),
+            ..Self::sudden_death(minutes)
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seconds = |secs: &str| {
+            secs.trim()
+                .parse()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| format!("invalid seconds `{secs}`"))
+        };
+        let minutes = |mins: &str| {
+            mins.trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|mins| Duration::try_from_secs_f64(mins * 60.0).ok())
+                .filter(|time| !time.is_zero())
+                .ok_or_else(|| format!("invalid minutes `{mins}`"))
+        };
+
+        let (s, delay) = match s.split_once('d') {
+            Some((s, delay)) => (s, Some(seconds(delay)?)),
+            None => (s, None),
+        };
+        let mut tokens = s.split('+');
+        let mut stages = Vec::new();
+        loop {
+            let Some(token) = tokens.next() else {
+                return Err("the last stage must be for the rest of the game".into());
+            };
+            let Some((moves, time)) = token.split_once('/') else {
+                stages.push(Stage {
+                    moves: None,
+                    time: minutes(token)?,
+                });
+                break;
+            };
+            let moves = moves
+                .trim()
+                .parse()
+                .ok()
+                .filter(|moves| *moves > 0)
+                .ok_or_else(|| format!("invalid number of moves `{moves}`"))?;
+            stages.push(Stage {
+                moves: Some(moves),
+                time: minutes(time)?,
+            });
+        }
+
+        let bonus = match (tokens.next(), delay) {
+            (Some(_), Some(_)) => return Err("an increment and a delay can't be both used".into()),
+            (Some(increment), None) => Bonus::Fischer(seconds(increment)?),
+            (None, Some(delay)) => Bonus::Bronstein(delay),
+            (None, None) => Bonus::None,
+        };
+        if let Some(token) = tokens.next() {
+            return Err(format!("unexpected `+{token}`").into());
+        }
+        Ok(Self { stages, bonus })
+    }
+}
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 {
+                write!(f, "+")?;
+            }
+            if let Some(moves) = stage.moves {
+                write!(f, "{moves}/")?;
+            }
+            write!(f, "{}", stage.time.as_secs_f64() / 60.0)?;
+        }
+        match self.bonus {
+            Bonus::None => Ok(()),
+            Bonus::Fischer(increment) => write!(f, "+{}", increment.as_secs_f64()),
+            Bonus::Bronstein(delay) => write!(f, "d{}", delay.as_secs_f64()),
+        }
+    }
+}
+
+/// The clocks of both players.
+///
+/// The clock doesn't measure the time by itself: [`Clock::press`] is told how long each move took.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Clock {
+    control: TimeControl,
+    /// Time left to White and Black
+    remaining: [Duration; 2],
+    /// Moves played by White and Black
+    moves: [u32; 2],
+}
+
+impl Clock {
+    #[inline]
+    pub fn new(control: TimeControl) -> Self {
+        let time = control.stages[0].time;
+        Self {
+            control,
+            remaining: [time; 2],
+            moves: [0; 2],
+        }
+    }
+    #[inline]
+    pub const fn control(&self) -> &TimeControl {
+        &self.control
+    }
+    /// Time left to `color`.
+    #[inline]
+    pub const fn remaining(&self, color: Color) -> Duration {
+        self.remaining[Self::side(color)]
+    }
+    /// Whether the time of `color` has run out.
+    #[inline]
+    pub const fn flagged(&self, color: Color) -> bool {
+        self.remaining(color).is_zero()
+    }
+    /// Moves `color` has to play before the end of the current stage,
+    /// `None` if the stage is the rest of the game.
+    pub fn moves_to_go(&self, color: Color) -> Option<u32> {
+        let played = self.moves[Self::side(color)];
+        let mut end = 0;
+        for stage in &self.control.stages {
+            end += stage.moves?;
+            if played < end {
+                return Some(end - played);
+            }
+        }
+        None
+    }
+    /// Stops the clock of `color` after a move which took `elapsed`,
+    /// giving the [`Bonus`] and the time of the next stage if it has begun.
+    ///
+    /// Returns `false` if the time ran out before the move, which then doesn't count.
+    pub fn press(&mut self, color: Color, elapsed: Duration) -> bool {
+        let side = Self::side(color);
+        if elapsed >= self.remaining[side] {
+            self.remaining[side] = Duration::ZERO;
+            return false;
+        }
+        let stage_ends = self.moves_to_go(color) == Some(1);
+
+        self.remaining[side] -= elapsed;
+        self.remaining[side] += match self.control.bonus {
+            Bonus::None => Duration::ZERO,
+            Bonus::Fischer(increment) => increment,
+            Bonus::Bronstein(delay) => elapsed.min(delay),
+        };
+        if stage_ends && let Some(next) = self.control.stages.get(self.stage(color) + 1) {
+            self.remaining[side] += next.time;
+        }
+        self.moves[side] += 1;
+        true
+    }
+    /// Index of the stage `color` is playing.
+    fn stage(&self, color: Color) -> usize {
+        let played = self.moves[Self::side(color)];
+        let mut end = 0;
+        for (i, stage) in self.control.stages.iter().enumerate() {
+            let Some(moves) = stage.moves else {
+                return i;
+            };
+            end += moves;
+            if played < end {
+                return i;
+            }
+        }
+        self.control.stages.len() - 1
+    }
+    #[inline(always)]
+    const fn side(color: Color) -> usize {
+        match color {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+}
+impl Display for Clock {
+    /// Writes the time left to both players (e.g. `White 4:59 | Black 5:00`),
+    /// with tenths of a second under 10 seconds.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = |time: Duration| {
+            let secs = time.as_secs();
+            let clock = if secs >= 3600 {
+                format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+            } else {
+                format!("{}:{:02}", secs / 60, secs % 60)
+            };
+            if secs < 10 {
+                format!("{clock}.{}", time.subsec_millis() / 100)
+            } else {
+                clock
+            }
+        };
+        write!(
+            f,
+            "White {} | Black {}",
+            time(self.remaining(Color::White)),
+            time(self.remaining(Color::Black))
+        )
+    }
+}
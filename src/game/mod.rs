@@ -1,69 +1,193 @@
 use {
     crate::{
-        chessboard::Board,
+        chessboard::{Board, UndoInfo, zobrist},
+        engine::{Engine, FIFTY_MOVES, Limits, evaluate},
         geomath::Point,
-        types::{Color, Movement, Pawn, piece_from_char},
+        protocol::{MOVES_TO_GO, time_for_move},
+        types::{CLASSICAL_SETUP, Color, Movement, Pawn},
     },
+    clock::{Bonus, Clock, TimeControl},
+    colored::Colorize as _,
+    indexmap::IndexMap,
     std::{
         error::Error,
+        fmt::{self, Display},
+        fs::OpenOptions,
         io::{self, Write},
+        time::{Duration, Instant},
     },
 };
 
+pub mod clock;
+
 const P1: &str = "White";
 const P2: &str = "Black";
 
+/// A move with the letter of the piece a pawn promotes to
+type MoveChoice = (Movement, Option<char>);
+
 #[inline(always)]
 fn p_name(color: Color) -> &'static str {
     if color.into() { P1 } else { P2 }
 }
 
 /// Engine of the game
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct Game {
     /// Score of pieces eaten by white
     w_score: f64,
     /// Score of pieces eaten by black
     b_score: f64,
-    /// Count for the 50-move rule
-    pub move_count: u8,
+    /// Plies since the last capture or pawn move, for the 50-move rule (see [`FIFTY_MOVES`])
+    pub move_count: u16,
     /// Chessboard
     pub board: Board,
     /// Turn of the game (White / Black)
     pub turn: Color,
+    /// Half-moves played since the start of the game (used for the full-move number)
+    pub ply: u32,
+    /// FEN of the starting position, `None` for the classical one
+    pub start_fen: Option<String>,
+    /// Moves played since the starting position
+    pub history: Vec<PlayedMove>,
+    /// Result of the game, `None` while it is being played
+    pub outcome: Option<Outcome>,
+    /// What is needed to take back each move of the history
+    undo_stack: Vec<Undo>,
+    /// Moves taken back, the last one is the first to be played again
+    redo_stack: Vec<PlayedMove>,
+    /// Who plays white
+    pub white: Player,
+    /// Who plays black
+    pub black: Player,
+    /// Limits of the search of the bot
+    pub limits: Limits,
+    /// Searches the moves of the bot, kept between them for its transposition table
+    /// (created at its first move)
+    bot: Option<Engine>,
+    /// Clocks of the players, `None` if the game isn't timed
+    ///
+    /// Taking back moves doesn't give back the time spent on them.
+    pub clock: Option<Clock>,
+}
+
+/// State of a [`Game`] before a move, to take it back
+#[derive(Debug)]
+struct Undo {
+    /// What the move changed on the board
+    board: UndoInfo,
+    /// Key of the position before the move (see [`Game::position`])
+    position: u64,
+    w_score: f64,
+    b_score: f64,
+    move_count: u16,
+}
+
+/// A move played during a [`Game`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayedMove {
+    pub movement: Movement,
+    /// Letter of the piece a pawn promoted to
+    pub promotion: Option<char>,
+    /// The move in Standard Algebraic Notation
+    pub san: String,
+}
+
+/// Who chooses the moves of a color
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Player {
+    /// A player on the terminal
+    #[default]
+    Human,
+    /// The [`Engine`]
+    Bot,
+}
+
+/// How a game ended
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    /// The player of the given color won
+    Win(Color),
+    Draw,
+}
+impl Display for Outcome {
+    /// Writes the result as in PGN (`1-0`, `0-1` or `1/2-1/2`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Win(Color::White) => write!(f, "1-0"),
+            Self::Win(Color::Black) => write!(f, "0-1"),
+            Self::Draw => write!(f, "1/2-1/2"),
+        }
+    }
 }
 
 impl Game {
+    /// A game starting from the [Chess960](crate::types::chess960_rank) setup numbered `index`.
+    ///
+    /// Panics if `index` is not less than [`CHESS960_POSITIONS`](crate::types::CHESS960_POSITIONS).
+    pub fn chess960(index: usize) -> Self {
+        let mut game = Self {
+            board: Board::chess960(index),
+            ..Self::default()
+        };
+        game.start_fen = (index != CLASSICAL_SETUP).then(|| game.to_fen());
+        game
+    }
     /// How to play the game.
     #[inline]
     fn print_instructions() {
         println!("Chess!\n");
         println!(
-            "To play, write the coordinates of the piece you want to move and \
+            "To play, write your move in Standard Algebraic Notation, \
+            or the coordinates of the piece you want to move and \
             then the coordinates where you want it to go"
         );
-        println!("Example:\nPiece coords: E2\nTo: E4\n\n");
+        println!("Examples:\nMove: Nf3\n\nMove: e2e4\n\nMove: E2\nTo: E4\n\n");
         println!(
             "To promote a Pawn, write the first letter of the piece you want \
-            to promote (B/N/R/Q)"
-        )
+            to promote (B/N/R/Q) after the move (e.g. e8=Q or e7e8q)"
+        );
+        println!(
+            "To take back moves or play them again, write undo or redo \
+            followed by the number of moves (1 if omitted)"
+        );
+        println!("To claim a draw when a position occurs for the third time, write draw\n")
     }
     /// Real score of white
     #[inline(always)]
-    const fn white_score(&self) -> f64 {
+    pub const fn white_score(&self) -> f64 {
         self.w_score - self.b_score
     }
     /// Real score of black
     #[inline(always)]
-    const fn black_score(&self) -> f64 {
+    pub const fn black_score(&self) -> f64 {
         self.b_score - self.w_score
     }
-    /// A string of the scores to be printed
+    /// A string of the scores (and of the clocks, if the game is timed) to be printed
     fn score_str(&self) -> String {
-        format!(
+        let score = format!(
             "[{}: {}]",
             p_name(self.turn),
             self.get_printable_score(self.turn),
+        );
+        match &self.clock {
+            Some(clock) => format!("{score} [{clock}]"),
+            None => score,
+        }
+    }
+    /// A bar of [`evaluate`] for White (in pawns), filled with the colors of the players
+    /// as they are better, to be printed
+    fn evaluation_bar(&self) -> String {
+        const WIDTH: i32 = 24;
+        /// Advantage filling the whole bar, in centipawns
+        const FULL: i32 = 1000;
+        let score = evaluate(&self.board, Color::White);
+        let white = (score.clamp(-FULL, FULL) + FULL) * WIDTH / (2 * FULL);
+        format!(
+            "{}{} {:+.2}",
+            "█".repeat(white as usize).bright_white(),
+            "█".repeat((WIDTH - white) as usize).bright_blue(),
+            f64::from(score) / 100.
         )
     }
     #[inline]
@@ -82,118 +206,413 @@ impl Game {
             self.black_score()
         }
     }
+    /// Plays a move for the player whose turn it is, updating the scores,
+    /// the 50-move rule count and the history of the game.
+    ///
+    /// The move must be legal (see [`Board::is_legal`]).
+    ///
+    /// `promotion` is the letter (B/N/R/Q) of the piece a promoting pawn becomes,
+    /// a Queen if it is `None`.
+    ///
+    /// The moves taken back with [`Game::undo`] can't be played again after this.
+    pub fn play(&mut self, mov: &Movement, promotion: Option<char>) {
+        self.redo_stack.clear();
+        self.push_move(mov, promotion);
+    }
+    fn push_move(&mut self, mov: &Movement, promotion: Option<char>) {
+        let promotion = self
+            .board
+            .is_promoting(mov)
+            .then(|| promotion.unwrap_or('Q').to_ascii_uppercase());
+        let san = mov.to_san(&mut self.board, promotion);
+        let position = self.position();
+        let (w_score, b_score, move_count) = (self.w_score, self.b_score, self.move_count);
+
+        self.fifty_moves(mov);
+        let board = self.board.make_move_promoting(mov, promotion);
+        if let Some((_, piece)) = &board.captured {
+            *self.get_mut_score(self.turn) += piece.score();
+        }
+
+        self.undo_stack.push(Undo {
+            board,
+            position,
+            w_score,
+            b_score,
+            move_count,
+        });
+        self.history.push(PlayedMove {
+            movement: mov.clone(),
+            promotion,
+            san,
+        });
+        self.turn = self.turn.opposite();
+        self.ply += 1;
+    }
+    /// Takes back the last move played, restoring the board, the scores
+    /// and the 50-move rule count as they were before it.
+    ///
+    /// Returns `false` if there is no move to take back.
+    pub fn undo(&mut self) -> bool {
+        let Some(undo) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.board.unmake_move(undo.board);
+        self.w_score = undo.w_score;
+        self.b_score = undo.b_score;
+        self.move_count = undo.move_count;
+
+        if let Some(played) = self.history.pop() {
+            self.redo_stack.push(played);
+        }
+        self.turn = self.turn.opposite();
+        self.ply -= 1;
+        self.outcome = None;
+        true
+    }
+    /// Plays again the last move taken back with [`Game::undo`].
+    ///
+    /// Returns `false` if there is no move to play again.
+    pub fn redo(&mut self) -> bool {
+        let Some(played) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.push_move(&played.movement, played.promotion);
+        self.outcome = self.end();
+        true
+    }
+    /// [Zobrist hash](Board::zobrist) of the position, including the player to move.
+    #[inline]
+    pub fn zobrist(&self) -> u64 {
+        match self.turn {
+            Color::White => self.board.zobrist(),
+            Color::Black => self.board.zobrist() ^ zobrist::black_to_move(),
+        }
+    }
+    /// [Key](Board::position_key) of the position, including the player to move.
+    ///
+    /// Positions with the same key are considered the same for the repetition rules.
+    #[inline]
+    fn position(&self) -> u64 {
+        match self.turn {
+            Color::White => self.board.position_key(),
+            Color::Black => self.board.position_key() ^ zobrist::black_to_move(),
+        }
+    }
+    /// Keys of the positions before the current one, since the last capture or pawn move,
+    /// for the [`Engine`] to see repetitions (see [`Engine::set_history`]).
+    pub fn reversible_positions(&self) -> Vec<u64> {
+        let start = self.undo_stack.len().saturating_sub(self.move_count.into());
+        self.undo_stack[start..]
+            .iter()
+            .map(|undo| undo.position)
+            .collect()
+    }
+    /// [`Board::perft`] of the current position, starting with the player to move.
+    #[inline]
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        self.board.perft(self.turn, depth)
+    }
+    /// [`Board::divide`] of the current position, starting with the player to move.
+    #[inline]
+    pub fn divide(&mut self, depth: usize) -> IndexMap<String, u64> {
+        self.board.divide(self.turn, depth)
+    }
+    /// How many times the current position has occurred during the game (at least 1).
+    pub fn repetitions(&self) -> usize {
+        let position = self.position();
+        1 + self
+            .undo_stack
+            .iter()
+            .filter(|undo| undo.position == position)
+            .count()
+    }
+    /// Whether the player to move can claim a draw,
+    /// because the current position has occurred 3 times.
+    #[inline]
+    pub fn can_claim_draw(&self) -> bool {
+        self.repetitions() >= 3
+    }
+    /// Ends the game in a draw if the player to move can claim it.
+    ///
+    /// Returns `false` if the draw can't be claimed.
+    pub fn claim_draw(&mut self) -> bool {
+        let can_claim = self.outcome.is_none() && self.can_claim_draw();
+        if can_claim {
+            self.outcome = Some(Outcome::Draw);
+        }
+        can_claim
+    }
+    /// Stops the clock of the player to move after they took `elapsed` to choose their move.
+    ///
+    /// Returns `false`, ending the game (see [`Game::timeout`]), if their time ran out:
+    /// the move must not be played then.
+    pub fn press_clock(&mut self, elapsed: Duration) -> bool {
+        let Some(clock) = &mut self.clock else {
+            return true;
+        };
+        let in_time = clock.press(self.turn, elapsed);
+        if !in_time {
+            self.outcome = Some(self.timeout(self.turn));
+        }
+        in_time
+    }
+    /// Outcome of the game when `color` runs out of time: the other player wins,
+    /// unless they couldn't checkmate anyway (see [`Board::insufficient_winning_material`]).
+    #[inline]
+    pub fn timeout(&self, color: Color) -> Outcome {
+        if self.board.insufficient_winning_material(color.opposite()) {
+            Outcome::Draw
+        } else {
+            Outcome::Win(color.opposite())
+        }
+    }
+    /// Limits of the search of the bot, which on a clock
+    /// also has to keep time for the next moves (see [`time_for_move`]).
+    pub fn bot_limits(&self) -> Limits {
+        let Some(clock) = &self.clock else {
+            return self.limits;
+        };
+        let increment = match clock.control().bonus {
+            Bonus::None => Duration::ZERO,
+            Bonus::Fischer(bonus) | Bonus::Bronstein(bonus) => bonus,
+        };
+        let time = time_for_move(
+            clock.remaining(self.turn),
+            increment,
+            clock.moves_to_go(self.turn).unwrap_or(MOVES_TO_GO),
+        );
+        Limits {
+            time: Some(self.limits.time.map_or(time, |limit| limit.min(time))),
+            ..self.limits
+        }
+    }
+    /// Returns the outcome of the game if the player to move
+    /// is checkmated or can't win anymore.
+    pub fn end(&self) -> Option<Outcome> {
+        if self.board.checkmate(self.turn) {
+            return Some(Outcome::Win(self.turn.opposite()));
+        }
+        // Stalemate, insufficient material, 50 moves rule or fivefold repetition
+        (self.board.stalemate(self.turn)
+            || self.board.insufficient_material()
+            || usize::from(self.move_count) >= FIFTY_MOVES
+            || self.repetitions() >= 5)
+            .then_some(Outcome::Draw)
+    }
     pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
         Self::print_instructions();
+        self.ask_players()?;
+        self.ask_clock()?;
 
-        loop {
+        // when the turn of the player to move started
+        let mut turn_start = (self.ply, Instant::now());
+        while self.outcome.is_none() {
+            if turn_start.0 != self.ply {
+                turn_start = (self.ply, Instant::now());
+            }
             println!("It's {}'s turn", p_name(self.turn));
             println!("{}", self.score_str());
+            println!("{}", self.evaluation_bar());
             println!("{}", self.board);
-
-            let mut from = String::new();
-            print!("Piece coords: ");
-            io::stdout().flush()?;
-            // .run() is directly returned from main, so we can use `?`
-            io::stdin().read_line(&mut from)?;
-
-            let mut to = String::new();
-            print!("To: ");
-            io::stdout().flush()?;
-            io::stdin().read_line(&mut to)?;
-
-            let from = from.trim();
-            let to = to.trim();
-            let Ok(from) = Point::try_from(from) else {
-                println!("Invalid input.\n");
-                continue;
-            };
-            let Ok(to) = Point::try_from(to) else {
-                println!("Invalid input.\n");
+            if self.player(self.turn) == Player::Bot {
+                self.bot_move(turn_start.1);
                 continue;
-            };
-
-            let Some(piece) = self.board[from].as_ref() else {
-                println!("Empty cell.");
-                continue;
-            };
+            }
+            if self.can_claim_draw() {
+                println!(
+                    "This position has occurred {} times, write draw to claim a draw.",
+                    self.repetitions()
+                );
+            }
 
-            if piece.color() != self.turn {
-                println!("Not your piece.");
+            let input = read_input("Move: ")?;
+            if self.command(&input) {
                 continue;
             }
-            let Some(movement) = self
-                .board
-                .filtered_move_set(from)
-                .into_iter()
-                .find(|mov| mov.from == from && mov.to == to)
-            else {
-                println!("Invalid move.");
+            let Some((movement, promotion)) = self.ask_move(&input)? else {
                 continue;
             };
+            if !self.press_clock(turn_start.1.elapsed()) {
+                break;
+            }
 
-            let piece = self
-                .board
-                .is_promoting(&movement)
-                .then(|| {
-                    loop {
-                        let Ok(upg) = ask_upgrade() else {
-                            println!("Invalid choice.");
-                            continue;
-                        };
-                        break piece_from_char(upg, piece.color(), piece.pos());
-                    }
-                })
-                .flatten();
-
-            {
-                // control if the move would lead to a check
-                let mut board = self.board.clone();
-                let mut score = self.get_printable_score(self.turn.opposite()); // score clone
-                let piece_clone = piece.as_ref().map(|p| p.clone_box());
-                if let Some((new_score, ..)) = board.do_move(&movement, piece_clone) {
-                    score -= new_score; // this will be seen by the losing player
-                }
+            self.play(&movement, promotion);
+            self.outcome = self.end();
+        }
 
-                if board.check(self.turn).is_some() {
-                    println!("Invalid move.");
-                    continue;
-                }
+        println!("{}", self.score_str());
+        println!("{}", self.board);
+        if self
+            .clock
+            .as_ref()
+            .is_some_and(|clock| clock.flagged(self.turn))
+        {
+            println!("{} ran out of time.", p_name(self.turn));
+            if self.outcome == Some(Outcome::Draw) {
+                println!("{} can't checkmate.", p_name(self.turn.opposite()));
+            }
+        }
+        match self.outcome {
+            Some(Outcome::Win(color)) => println!("{} lost.", p_name(color.opposite())),
+            _ => println!("It's a tie."),
+        }
 
-                if board.checkmate(self.turn.opposite()) {
-                    println!("[{}: {}]", p_name(self.turn.opposite()), score);
-                    println!("{board}");
-                    println!("{} lost.", p_name(self.turn.opposite()));
-                    break;
-                }
-                if board.stalemate(self.turn.opposite()) {
-                    println!("{board}");
-                    println!("It's a tie.");
-                    break;
-                }
+        self.save_pgn()
+    }
+    /// Who plays `color`
+    #[inline]
+    pub const fn player(&self, color: Color) -> Player {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+    /// Asks who plays: two players, a player against the bot (of the chosen color)
+    /// or the bot against itself.
+    fn ask_players(&mut self) -> io::Result<()> {
+        println!("1. Human vs Human\n2. Human vs Bot\n3. Bot vs Bot");
+        (self.white, self.black) = loop {
+            match read_input("Choose the players (1/2/3): ")?.as_str() {
+                "1" => break (Player::Human, Player::Human),
+                "2" => break Self::ask_bot_color()?,
+                "3" => break (Player::Bot, Player::Bot),
+                _ => println!("Invalid choice."),
             }
-            // Only kings on the board
-            if self.board.all_pieces().len() == 2 {
-                println!("{}", self.score_str());
-                println!("{}", self.board);
-                println!("It's a tie.");
-                break;
+        };
+        println!();
+        Ok(())
+    }
+    /// Asks the time control of the game, if it is timed.
+    fn ask_clock(&mut self) -> io::Result<()> {
+        self.clock = loop {
+            let input = read_input(
+                "Time control in minutes, e.g. 5+3 (increment), 5d3 (delay), \
+                40/90+30 (stages), leave empty for none: ",
+            )?;
+            if input.is_empty() {
+                break None;
             }
-
-            // 50 moves rule's count
-            if self.fifty_moves(&movement) {
-                println!("{}", self.score_str());
-                println!("{}", self.board);
-                println!("It's a tie.");
-                break;
+            match input.parse::<TimeControl>() {
+                Ok(control) => break Some(Clock::new(control)),
+                Err(err) => println!("Invalid time control: {err}."),
+            }
+        };
+        println!();
+        Ok(())
+    }
+    /// Asks the color of the bot, returning who plays white and black.
+    fn ask_bot_color() -> io::Result<(Player, Player)> {
+        loop {
+            match read_input("Color of the bot (W/B): ")?
+                .to_ascii_uppercase()
+                .as_str()
+            {
+                "W" => return Ok((Player::Bot, Player::Human)),
+                "B" => return Ok((Player::Human, Player::Bot)),
+                _ => println!("Invalid choice."),
             }
+        }
+    }
+    /// Lets the [`Engine`] play for the player whose turn it is, which started at `turn_start`.
+    fn bot_move(&mut self, turn_start: Instant) {
+        let (limits, history) = (self.bot_limits(), self.reversible_positions());
+        let engine = self.bot.get_or_insert_with(|| Engine::new(limits));
+        engine.set_limits(limits);
+        engine.set_history(history, self.move_count.into());
+        let Some(result) = engine.search(&self.board, self.turn) else {
+            // no legal moves
+            self.outcome = Some(if self.board.check(self.turn).is_some() {
+                Outcome::Win(self.turn.opposite())
+            } else {
+                Outcome::Draw
+            });
+            return;
+        };
+        if !self.press_clock(turn_start.elapsed()) {
+            return;
+        }
+        let san = result.movement.to_san(&mut self.board, result.promotion);
+        println!("{} plays {san}\n", p_name(self.turn));
 
-            if let Some((score, color)) = self.board.do_move(&movement, piece) {
-                *self.get_mut_score(color) += score;
+        self.play(&result.movement, result.promotion);
+        self.outcome = self.end();
+    }
+    /// Asks the player whose turn it is for a legal move, either in SAN (`Nf3`)
+    /// or in coordinates (`g1f3`, `e7e8q`).
+    ///
+    /// If only the square of one of their pieces is written,
+    /// the square where it goes is asked next.
+    ///
+    /// Returns `None` (after telling why) if the input is not a legal move.
+    fn ask_move(&self, input: &str) -> Result<Option<MoveChoice>, Box<dyn Error>> {
+        // A square with a piece of the player can't be the destination of a SAN move
+        let coords = input
+            .get(..2)
+            .and_then(|from| Point::try_from(from).ok())
+            .is_some_and(|from| {
+                self.board[from]
+                    .as_ref()
+                    .is_some_and(|piece| piece.color() == self.turn)
+            });
+        let parsed = if !coords {
+            Movement::from_san(&self.board, self.turn, input)
+        } else if input.len() == 2 {
+            let to = read_input("To: ")?;
+            Movement::from_uci(&self.board, self.turn, &format!("{input}{to}"))
+        } else {
+            Movement::from_uci(&self.board, self.turn, input)
+        };
+
+        Ok(match parsed {
+            Ok(mov) => Some(mov),
+            Err(err) => {
+                println!("Invalid move: {err}.\n");
+                None
+            }
+        })
+    }
+    /// Runs `draw`, or `undo` / `redo` optionally followed by the number of moves.
+    ///
+    /// Returns `false` if the input is not a command.
+    fn command(&mut self, input: &str) -> bool {
+        let mut words = input.split_whitespace();
+        let name = match words.next() {
+            Some("draw") => {
+                if !self.claim_draw() {
+                    println!("A draw can only be claimed after a threefold repetition.\n");
+                }
+                return true;
             }
+            Some(name @ ("undo" | "redo")) => name,
+            _ => return false,
+        };
+        let Ok(n) = words.next().map_or(Ok(1), str::parse::<usize>) else {
+            println!("Invalid number of moves.\n");
+            return true;
+        };
 
-            self.turn = self.turn.opposite();
+        let action = if name == "undo" {
+            Self::undo
+        } else {
+            Self::redo
+        };
+        if (0..n).take_while(|_| action(self)).count() < n {
+            println!("No more moves to {name}.\n");
         }
+        true
+    }
+    /// Prints the game as PGN and lets the players save it to a file.
+    fn save_pgn(&self) -> Result<(), Box<dyn Error>> {
+        let pgn = self.to_pgn();
+        println!("\n{pgn}");
 
+        let path = read_input("Save the game to a PGN file (leave empty to skip): ")?;
+        if !path.is_empty() {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{pgn}")?;
+            println!("Game saved to {path}.");
+        }
         Ok(())
     }
     /// true: game ends
@@ -204,31 +623,21 @@ impl Game {
         // 50 moves rule's count
         let from_piece = (**self.board[mov.from].as_ref().unwrap()).as_any();
 
-        if from_piece.is::<Pawn>() || self.board[mov.to].is_some() {
+        if from_piece.is::<Pawn>() || self.board.captured_square(mov).is_some() {
             self.move_count = 0;
         } else {
-            self.move_count += 1;
+            self.move_count = self.move_count.saturating_add(1);
         }
         // Tie by 50 moves rule
-        self.move_count >= 50
+        usize::from(self.move_count) >= FIFTY_MOVES
     }
 }
-#[cfg(test)] // during tests, we can't ask input
-#[inline]
-pub fn ask_upgrade() -> Result<char, Box<dyn Error>> {
-    Ok('Q')
-}
-#[cfg(not(test))]
-pub fn ask_upgrade() -> Result<char, Box<dyn Error>> {
-    println!("Pawn's got to the last row.");
-
+/// Prints `prompt` and reads a trimmed line from the terminal.
+fn read_input(prompt: &str) -> io::Result<String> {
     let mut input = String::new();
-    print!("Choose a piece to upgrade: (B/N/R/Q) ");
-    io::stdout().flush().unwrap();
-    io::stdin().read_line(&mut input).unwrap();
-
-    match input.as_str() {
-        "B" | "N" | "R" | "Q" => Ok(input.chars().next().unwrap()),
-        _ => Err("Invalid input".into()),
-    }
+    print!("{prompt}");
+    io::stdout().flush()?;
+    // .run() is directly returned from main, so errors can be propagated
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
 }
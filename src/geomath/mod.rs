@@ -6,8 +6,8 @@ mod tests;
 
 use {
     crate::{chessboard::Board, geomath::rotation::Direction},
+    indexmap::IndexSet,
     std::{
-        collections::HashSet,
         error::Error,
         fmt::{self, Display},
         ops::{Add, AddAssign, Mul, Neg, Sub},
@@ -29,13 +29,21 @@ impl Point {
     pub const fn new(x: isize, y: isize) -> Self {
         Self { x, y }
     }
+    /// Name of the square in algebraic notation (e.g. `e4`).
+    ///
+    /// The point must be inside the [`Board`].
+    pub fn algebraic(self) -> String {
+        assert!(Board::in_bounds(self), "(x, y): {self}");
+        format!("{}{}", (b'a' + self.x as u8) as char, self.y + 1)
+    }
 
-    pub fn rotations(self) -> HashSet<(Self, Option<Direction>)> {
+    /// The point turned by 90 degrees and mirrored, each one once, in the same order on every run.
+    pub fn rotations(self) -> IndexSet<(Self, Option<Direction>)> {
         let Point { x, y } = self;
         let iter = [(x, y), (-x, y)].map(Self::from).into_iter();
         let iter = iter.clone().chain(iter.map(Neg::neg));
 
-        let set: HashSet<Self> = iter
+        let set: IndexSet<Self> = iter
             .clone()
             .chain(iter.map(|Point { x, y }| Self::new(y, x)))
             .collect();
@@ -43,7 +51,7 @@ impl Point {
         set.into_iter().map(|point| (point, point.into())).collect()
     }
     /// Returns all the cells in a square (l = 2 * offset + 1) around (0, 0)
-    pub fn all_around(offset: isize) -> HashSet<(Self, Option<Direction>)> {
+    pub fn all_around(offset: isize) -> IndexSet<(Self, Option<Direction>)> {
         Self::new(offset, offset)
             .rotations()
             .into_iter()
@@ -121,7 +129,7 @@ impl From<(isize, isize)> for Point {
 impl TryFrom<&str> for Point {
     type Error = Box<dyn Error>;
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let s: Vec<char> = s.chars().collect();
+        let s: Vec<char> = s.trim().chars().collect();
 
         if s.len() != 2 {
             return Err("invalid length".into());
@@ -141,9 +149,10 @@ impl TryFrom<&str> for Point {
             .ok_or("invalid coords")?
             .0 as isize;
 
-        let y = y.to_string().parse::<isize>()? - 1; // can't be negative because it's just 1 char
+        let y = y.to_string().parse::<isize>()? - 1; // -1 for rank 0, as it's just 1 char
 
-        (y < Board::SIZE as isize)
+        (0..Board::SIZE as isize)
+            .contains(&y)
             .then_some(Point::new(x, y))
             .ok_or("invalid coords".into())
     }
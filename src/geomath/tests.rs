@@ -1,7 +1,4 @@
-use {
-    crate::prelude::*,
-    std::{collections::HashSet, error::Error},
-};
+use {crate::prelude::*, indexmap::IndexSet, std::error::Error};
 
 #[test]
 fn point() {
@@ -41,6 +38,7 @@ fn point_from_str() -> Result<(), Box<dyn Error>> {
         Point::try_from(String::from("A3").as_str())?,
         Point::new(0, 2)
     );
+    assert_eq!(Point::try_from(" g6\n")?, Point::new(6, 5));
 
     Ok(())
 }
@@ -51,6 +49,11 @@ fn p_from_str_num_out_of_bounds() {
 }
 #[test]
 #[should_panic]
+fn point_from_str_rank_zero() {
+    let _ = Point::try_from("a0").unwrap();
+}
+#[test]
+#[should_panic]
 fn point_from_str_letter_out_of_bounds() {
     let _ = Point::try_from("j3").unwrap();
 }
@@ -91,17 +94,21 @@ fn point_neg() {
 #[test]
 fn knight_rots() {
     let rots = Point::new(1, 2).rotations();
-    let res = HashSet::from([
+    let res = IndexSet::from([
         (Point::new(1, 2), None),
         (Point::new(-1, 2), None),
-        (Point::new(1, -2), None),
         (Point::new(-1, -2), None),
+        (Point::new(1, -2), None),
         (Point::new(2, 1), None),
-        (Point::new(-2, 1), None),
         (Point::new(2, -1), None),
         (Point::new(-2, -1), None),
+        (Point::new(-2, 1), None),
     ]);
-    assert_eq!(rots, res);
+    // the same moves, in the same order
+    assert_eq!(
+        rots.into_iter().collect::<Vec<_>>(),
+        res.into_iter().collect::<Vec<_>>()
+    );
 }
 
 #[test]
@@ -117,39 +124,56 @@ fn opposite() {
 #[test]
 fn bishop_rots() {
     let rots = Point::new(1, 1).rotations();
-    let res = HashSet::from([
+    let res = IndexSet::from([
         (Point::new(1, 1), Some(Direction::UpRight)),
         (Point::new(-1, 1), Some(Direction::UpLeft)),
-        (Point::new(1, -1), Some(Direction::DownRight)),
         (Point::new(-1, -1), Some(Direction::DownLeft)),
+        (Point::new(1, -1), Some(Direction::DownRight)),
     ]);
-    assert_eq!(rots, res);
+    assert_eq!(
+        rots.into_iter().collect::<Vec<_>>(),
+        res.into_iter().collect::<Vec<_>>()
+    );
 }
 
 #[test]
 fn rook_rots() {
     let rots = Point::new(0, 1).rotations();
-    let res = HashSet::from([
+    let res = IndexSet::from([
         (Point::new(0, 1), Some(Direction::Up)),
-        (Point::new(1, 0), Some(Direction::Right)),
         (Point::new(0, -1), Some(Direction::Down)),
+        (Point::new(1, 0), Some(Direction::Right)),
         (Point::new(-1, 0), Some(Direction::Left)),
     ]);
-    assert_eq!(rots, res);
+    assert_eq!(
+        rots.into_iter().collect::<Vec<_>>(),
+        res.into_iter().collect::<Vec<_>>()
+    );
 }
 
 #[test]
 fn king_rots() {
     let rots = Point::all_around(1);
-    let res = HashSet::from([
-        (Point::new(0, 1), Some(Direction::Up)),
+    let res = IndexSet::from([
         (Point::new(1, 1), Some(Direction::UpRight)),
-        (Point::new(1, 0), Some(Direction::Right)),
-        (Point::new(1, -1), Some(Direction::DownRight)),
-        (Point::new(0, -1), Some(Direction::Down)),
+        (Point::new(-1, 1), Some(Direction::UpLeft)),
         (Point::new(-1, -1), Some(Direction::DownLeft)),
+        (Point::new(1, -1), Some(Direction::DownRight)),
+        (Point::new(1, 0), Some(Direction::Right)),
         (Point::new(-1, 0), Some(Direction::Left)),
-        (Point::new(-1, 1), Some(Direction::UpLeft)),
+        (Point::new(0, 1), Some(Direction::Up)),
+        (Point::new(0, -1), Some(Direction::Down)),
     ]);
-    assert_eq!(rots, res);
+    assert_eq!(
+        rots.into_iter().collect::<Vec<_>>(),
+        res.into_iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn point_algebraic() -> Result<(), Box<dyn Error>> {
+    assert_eq!(Point::new(0, 0).algebraic(), "a1");
+    assert_eq!(Point::new(7, 7).algebraic(), "h8");
+    assert_eq!(Point::try_from("E4")?.algebraic(), "e4");
+    Ok(())
 }
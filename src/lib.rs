@@ -1,6 +1,9 @@
 pub mod chessboard;
+pub mod engine;
 pub mod game;
 pub mod geomath;
+pub mod notation;
+pub mod protocol;
 
 pub mod prelude;
 pub mod types;
@@ -1,5 +1,29 @@
-use {rusty_chess::game::Game, std::error::Error};
+use {
+    rusty_chess::{
+        game::Game,
+        types::{CHESS960_POSITIONS, random_chess960},
+    },
+    std::{env, error::Error, fs},
+};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    Game::default().run()
+    let mut args = env::args().skip(1);
+    let mut game = match args.next().as_deref() {
+        // Chess960 from the given setup (0-959), or a random one
+        Some("--chess960") => {
+            let index = match args.next() {
+                Some(index) => index
+                    .parse()
+                    .ok()
+                    .filter(|index| *index < CHESS960_POSITIONS)
+                    .ok_or_else(|| format!("invalid Chess960 setup `{index}`"))?,
+                None => random_chess960(),
+            };
+            Game::chess960(index)
+        }
+        // A game saved as PGN can be given to replay and continue it
+        Some(path) => Game::from_pgn(&fs::read_to_string(path)?)?,
+        None => Game::default(),
+    };
+    game.run()
 }
@@ -0,0 +1,441 @@
+//! [Forsyth–Edwards Notation](https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation)
+//! import and export of a position.
+
+use {
+    crate::{chessboard::Board, game::Game, geomath::Point, types::*},
+    std::{
+        error::Error,
+        fmt::{self, Display},
+    },
+};
+
+/// FEN of the classical starting position.
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Castling rights in the order they are written in a FEN string.
+const CASTLES: [(char, Color, SpecialMove); 4] = [
+    ('K', Color::White, SpecialMove::ShortCastle),
+    ('Q', Color::White, SpecialMove::LongCastle),
+    ('k', Color::Black, SpecialMove::ShortCastle),
+    ('q', Color::Black, SpecialMove::LongCastle),
+];
+
+/// One of the six space-separated fields of a FEN string.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FenField {
+    /// Pieces on the board, rank by rank (e.g. `rnbqkbnr/pppppppp/8/...`)
+    Placement,
+    /// Side to move (`w` / `b`)
+    ActiveColor,
+    /// Castling rights (e.g. `KQkq` or `-`)
+    Castling,
+    /// Square behind a pawn which has just done a double move (e.g. `e3` or `-`)
+    EnPassant,
+    /// Half-moves since the last capture or pawn move (50-move rule)
+    HalfmoveClock,
+    /// Number of the full move, starting from 1
+    FullmoveNumber,
+}
+impl Display for FenField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Placement => "piece placement",
+            Self::ActiveColor => "active color",
+            Self::Castling => "castling availability",
+            Self::EnPassant => "en passant target square",
+            Self::HalfmoveClock => "halfmove clock",
+            Self::FullmoveNumber => "fullmove number",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Error returned when a FEN string cannot be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FenError {
+    /// The field which is malformed
+    pub field: FenField,
+    /// What is wrong with it
+    pub reason: String,
+}
+impl FenError {
+    #[inline]
+    fn new(field: FenField, reason: impl Into<String>) -> Self {
+        Self {
+            field,
+            reason: reason.into(),
+        }
+    }
+}
+impl Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid FEN {}: {}", self.field, self.reason)
+    }
+}
+impl Error for FenError {}
+
+/// Returns a `Box<dyn Piece>` from its FEN [`char`]
+/// (uppercase for white pieces, lowercase for black ones).
+fn piece_from_fen(c: char, pos: Point) -> Option<Box<dyn Piece>> {
+    let color = Color::from(c.is_ascii_uppercase());
+    match c.to_ascii_uppercase() {
+        'P' => Some(Box::new(Pawn::new(color, pos))),
+        'K' => Some(Box::new(King::new(color, pos))),
+        _ => piece_from_char(c, color, pos),
+    }
+}
+
+impl Board {
+    /// Constructor of `Board` from a FEN string.
+    ///
+    /// The side to move and the clocks are validated but discarded,
+    /// use [`Game::from_fen`] to keep them.
+    #[inline]
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Game::from_fen(fen).map(|game| game.board)
+    }
+    /// Piece placement field of the FEN of this board.
+    pub fn fen_placement(&self) -> String {
+        let mut fen = String::new();
+        for y in (0..Board::SIZE).rev() {
+            if y < Board::SIZE - 1 {
+                fen.push('/');
+            }
+            let mut empty = 0;
+            for square in &self[y] {
+                let Some(piece) = square else {
+                    empty += 1;
+                    continue;
+                };
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                fen.push(if piece.color().into() {
+                    piece.symbol()
+                } else {
+                    piece.symbol().to_ascii_lowercase()
+                });
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+        }
+        fen
+    }
+    /// Castling availability field of the FEN of this board, derived from the
+    /// [`PieceState`] of the Kings and the Rooks.
+    ///
+    /// As in X-FEN, a Rook which is not the outermost one on its side of the King
+    /// (only possible in Chess960) is written as its file (e.g. `Bk`).
+    pub fn fen_castling(&self) -> String {
+        self.castling_field(false)
+    }
+    /// Castling availability field in Shredder-FEN: the files of the Rooks (e.g. `HAha`).
+    pub fn shredder_fen_castling(&self) -> String {
+        self.castling_field(true)
+    }
+    fn castling_field(&self, shredder: bool) -> String {
+        let fen: String = CASTLES
+            .iter()
+            .filter_map(|(c, color, castle)| {
+                let rook = self.castling_rook(*color, castle)?;
+                let outer = match castle {
+                    SpecialMove::ShortCastle => rook.x + 1..Board::SIZE as isize,
+                    _ => 0..rook.x,
+                };
+                let outermost = !outer.into_iter().any(|x| {
+                    self[Point::new(x, rook.y)]
+                        .as_ref()
+                        .is_some_and(|piece| piece.color() == *color && piece.symbol() == 'R')
+                });
+                if outermost && !shredder {
+                    return Some(*c);
+                }
+                let file = (b'a' + rook.x as u8) as char;
+                Some(if (*color).into() {
+                    file.to_ascii_uppercase()
+                } else {
+                    file
+                })
+            })
+            .collect();
+        if fen.is_empty() { "-".into() } else { fen }
+    }
+    /// En passant target square field of the FEN of this board, derived from the
+    /// pawn in [`PawnState::JustDouble`].
+    pub fn fen_en_passant(&self) -> String {
+        self.all_pieces()
+            .into_iter()
+            .map(|pos| self[pos].as_ref().unwrap())
+            .find(|piece| {
+                piece.as_any().is::<Pawn>() && piece.is_state(PawnState::JustDouble.into())
+            })
+            .map_or("-".into(), |pawn| {
+                (pawn.pos() - Point::new(0, pawn.color().sign())).algebraic()
+            })
+    }
+}
+
+impl Game {
+    /// Constructor of `Game` from a FEN string.
+    ///
+    /// The halfmove clock and the fullmove number may be omitted,
+    /// in which case they default to `0` and `1`.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() > 6 {
+            return Err(FenError::new(
+                FenField::FullmoveNumber,
+                "unexpected data after the last field",
+            ));
+        }
+        let field = |i: usize, name: FenField| {
+            fields
+                .get(i)
+                .copied()
+                .ok_or_else(|| FenError::new(name, "missing field"))
+        };
+
+        let mut board = parse_placement(field(0, FenField::Placement)?)?;
+
+        let turn = match field(1, FenField::ActiveColor)? {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => {
+                return Err(FenError::new(
+                    FenField::ActiveColor,
+                    format!("expected `w` or `b`, found `{other}`"),
+                ));
+            }
+        };
+
+        parse_castling(&mut board, field(2, FenField::Castling)?)?;
+        parse_en_passant(&mut board, field(3, FenField::EnPassant)?, turn)?;
+
+        let move_count = match fields.get(4) {
+            Some(clock) => clock
+                .parse::<u16>()
+                .map_err(|e| FenError::new(FenField::HalfmoveClock, format!("`{clock}`: {e}")))?,
+            None => 0,
+        };
+        let fullmove = match fields.get(5) {
+            Some(number) => number
+                .parse::<u32>()
+                .ok()
+                .filter(|n| (1..=u32::MAX / 2).contains(n))
+                .ok_or_else(|| {
+                    FenError::new(
+                        FenField::FullmoveNumber,
+                        format!("`{number}` is not a positive number"),
+                    )
+                })?,
+            None => 1,
+        };
+
+        let mut game = Game::default();
+        game.board = board;
+        game.board.rehash();
+        game.turn = turn;
+        game.move_count = move_count;
+        game.ply = (fullmove - 1) * 2 + u32::from(!bool::from(turn));
+        game.start_fen = Some(game.to_fen());
+        Ok(game)
+    }
+    /// FEN string of the current position.
+    ///
+    /// The castling rights are written as in X-FEN, which is the same as FEN
+    /// for the classical setup, see [`Board::fen_castling`].
+    pub fn to_fen(&self) -> String {
+        self.fen_with_castling(self.board.fen_castling())
+    }
+    /// FEN string of the current position, with the castling rights
+    /// written as in Shredder-FEN, see [`Board::shredder_fen_castling`].
+    pub fn to_shredder_fen(&self) -> String {
+        self.fen_with_castling(self.board.shredder_fen_castling())
+    }
+    fn fen_with_castling(&self, castling: String) -> String {
+        format!(
+            "{} {} {castling} {} {} {}",
+            self.board.fen_placement(),
+            if self.turn.into() { 'w' } else { 'b' },
+            self.board.fen_en_passant(),
+            self.move_count,
+            self.ply / 2 + 1,
+        )
+    }
+}
+
+/// Builds the board from the piece placement field.
+///
+/// Pawns which are not on their starting row can't do a double move anymore,
+/// and Kings and Rooks are considered already moved until [`parse_castling`] says otherwise.
+fn parse_placement(field: &str) -> Result<Board, FenError> {
+    let err = |reason: String| FenError::new(FenField::Placement, reason);
+
+    let rows: Vec<&str> = field.split('/').collect();
+    if rows.len() != Board::SIZE {
+        return Err(err(format!(
+            "expected {} ranks, found {}",
+            Board::SIZE,
+            rows.len()
+        )));
+    }
+
+    let mut board = Board::empty();
+    for (i, row) in rows.into_iter().enumerate() {
+        let y = (Board::SIZE - 1 - i) as isize;
+        let rank = y + 1;
+        let mut x = 0;
+        for c in row.chars() {
+            if x >= Board::SIZE as isize {
+                return Err(err(format!("too many squares in rank {rank}")));
+            }
+            if let Some(empty) = c.to_digit(10) {
+                if !(1..=Board::SIZE as u32).contains(&empty) {
+                    return Err(err(format!("invalid empty squares count `{c}`")));
+                }
+                x += empty as isize;
+                continue;
+            }
+            let pos = Point::new(x, y);
+            let mut piece =
+                piece_from_fen(c, pos).ok_or_else(|| err(format!("unknown piece `{c}`")))?;
+
+            if piece.as_any().is::<Pawn>() {
+                if y == 0 || y == Board::SIZE as isize - 1 {
+                    return Err(err(format!("pawn on rank {rank}")));
+                }
+                if y != piece.color().second_row() as isize {
+                    piece.set_state(PawnState::Already.into());
+                }
+            } else if piece.as_any().is::<King>() || piece.as_any().is::<Rook>() {
+                piece.set_state(PieceState::Already.into());
+            }
+            board[pos] = Some(piece);
+            x += 1;
+        }
+        if x != Board::SIZE as isize {
+            return Err(err(format!(
+                "rank {rank} does not have {} squares",
+                Board::SIZE
+            )));
+        }
+    }
+
+    for color in [Color::White, Color::Black] {
+        let kings = board
+            .all_pieces()
+            .into_iter()
+            .filter(|pos| {
+                let piece = board[*pos].as_ref().unwrap();
+                piece.as_any().is::<King>() && piece.color() == color
+            })
+            .count();
+        if kings != 1 {
+            return Err(err(format!("expected one {color:?} king, found {kings}")));
+        }
+    }
+
+    Ok(board)
+}
+
+/// Gives back the right to castle to the Kings and Rooks listed in the castling field.
+///
+/// Besides `KQkq`, which are the outermost Rooks of each side of the King,
+/// the files of the Rooks are accepted as in X-FEN and Shredder-FEN (e.g. `HAha`, `Kq`, `Bk`),
+/// so that the King can be anywhere on its first row (Chess960).
+fn parse_castling(board: &mut Board, field: &str) -> Result<(), FenError> {
+    let err = |reason: String| FenError::new(FenField::Castling, reason);
+    if field == "-" {
+        return Ok(());
+    }
+
+    let mut seen = Vec::new();
+    for c in field.chars() {
+        let color = Color::from(c.is_ascii_uppercase());
+        let row = color.first_row() as isize;
+        let own = |x: isize| {
+            board[Point::new(x, row)]
+                .as_ref()
+                .filter(|piece| piece.color() == color)
+                .map(|piece| piece.symbol())
+        };
+        let Some(king) = (0..Board::SIZE as isize).find(|x| own(*x) == Some('K')) else {
+            return Err(err(format!(
+                "`{c}` needs the {color:?} king on its first row"
+            )));
+        };
+        let files = 0..Board::SIZE as isize;
+        let (castle, rook) = match c.to_ascii_uppercase() {
+            'K' => (
+                SpecialMove::ShortCastle,
+                files.rev().find(|x| *x > king && own(*x) == Some('R')),
+            ),
+            'Q' => (
+                SpecialMove::LongCastle,
+                files
+                    .into_iter()
+                    .find(|x| *x < king && own(*x) == Some('R')),
+            ),
+            file @ 'A'..='H' => {
+                let x = file as isize - 'A' as isize;
+                let castle = if x > king {
+                    SpecialMove::ShortCastle
+                } else {
+                    SpecialMove::LongCastle
+                };
+                (castle, (x != king && own(x) == Some('R')).then_some(x))
+            }
+            _ => return Err(err(format!("unknown castling right `{c}`"))),
+        };
+        if seen.contains(&(color, castle.clone())) {
+            return Err(err(format!("duplicated castling right `{c}`")));
+        }
+        let Some(rook) = rook else {
+            return Err(err(format!(
+                "`{c}` needs a {color:?} rook on the side of its king"
+            )));
+        };
+
+        for x in [king, rook] {
+            board[Point::new(x, row)]
+                .as_mut()
+                .unwrap()
+                .set_state(PieceState::NotYet.into());
+        }
+        seen.push((color, castle));
+    }
+    Ok(())
+}
+
+/// Marks the pawn which can be eaten En Passant as [`PawnState::JustDouble`].
+fn parse_en_passant(board: &mut Board, field: &str, turn: Color) -> Result<(), FenError> {
+    let err = |reason: String| FenError::new(FenField::EnPassant, reason);
+    if field == "-" {
+        return Ok(());
+    }
+
+    let target = Point::try_from(field).map_err(|e| err(format!("`{field}`: {e}")))?;
+    let pawn_color = turn.opposite();
+    let pawn_pos = target + Point::new(0, pawn_color.sign());
+    let start_pos = target - Point::new(0, pawn_color.sign());
+
+    if target.y != pawn_color.second_row() as isize + pawn_color.sign() {
+        return Err(err(format!(
+            "`{field}` is not a square a {pawn_color:?} pawn can skip"
+        )));
+    }
+    if board[target].is_some() || board[start_pos].is_some() {
+        return Err(err(format!("`{field}` is not empty")));
+    }
+    match board[pawn_pos].as_mut() {
+        Some(pawn) if pawn.color() == pawn_color && pawn.as_any().is::<Pawn>() => {
+            pawn.set_state(PawnState::JustDouble.into());
+            Ok(())
+        }
+        _ => Err(err(format!(
+            "no {pawn_color:?} pawn has just moved through `{field}`"
+        ))),
+    }
+}
@@ -0,0 +1,10 @@
+//! Standard chess notations, used to share positions and games with other tools.
+
+pub mod fen;
+pub mod pgn;
+mod san;
+#[cfg(test)]
+mod tests;
+mod uci;
+
+pub use {fen::*, pgn::*};
@@ -0,0 +1,476 @@
+//! [Portable Game Notation](https://en.wikipedia.org/wiki/Portable_Game_Notation)
+//! reading and writing of whole games.
+
+use {
+    crate::{
+        game::{Game, Outcome},
+        notation::{FenError, START_FEN},
+        types::{Color, Movement},
+    },
+    indexmap::IndexMap,
+    std::{
+        error::Error,
+        fmt::{self, Display},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Tags every PGN game has, in the order they are written.
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+/// Maximum length of a line of movetext when writing.
+const LINE_WIDTH: usize = 80;
+/// Move suffix annotations and the NAG they stand for.
+const SUFFIXES: [(&str, u8); 6] = [
+    ("!", 1),
+    ("?", 2),
+    ("!!", 3),
+    ("??", 4),
+    ("!?", 5),
+    ("?!", 6),
+];
+
+/// An element of the movetext of a game.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MoveText {
+    /// A move in Standard Algebraic Notation
+    Move(String),
+    /// Numeric Annotation Glyph of the previous move (`$1`, or `!`, `?` ...)
+    Nag(u8),
+    /// `{...}` or `;...` comment
+    Comment(String),
+    /// Alternative line to the previous move
+    Variation(Vec<MoveText>),
+}
+
+/// A game in Portable Game Notation.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Pgn {
+    /// Tag pairs, in the order they were read
+    pub tags: IndexMap<String, String>,
+    /// Moves of the main line with their comments, NAGs and variations
+    pub movetext: Vec<MoveText>,
+    /// Result of the game, `None` if it is unknown or still being played (`*`)
+    pub result: Option<Outcome>,
+}
+
+/// Error returned when a PGN game cannot be read or replayed.
+#[derive(Debug)]
+pub enum PgnError {
+    /// The text is not valid PGN
+    Syntax(String),
+    /// The `FEN` tag is not a valid position
+    Fen(FenError),
+    /// A move of the main line can't be played
+    IllegalMove {
+        /// Number of the half-move in the game, starting from 1
+        /// (after the half-moves before the position of the `FEN` tag)
+        ply: usize,
+        /// The move as written in the PGN
+        san: String,
+        /// Why the move can't be played
+        reason: String,
+    },
+}
+impl Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(reason) => write!(f, "invalid PGN: {reason}"),
+            Self::Fen(err) => write!(f, "invalid PGN FEN tag: {err}"),
+            Self::IllegalMove { ply, san, reason } => {
+                write!(f, "illegal move `{san}` at ply {ply}: {reason}")
+            }
+        }
+    }
+}
+impl Error for PgnError {}
+impl From<FenError> for PgnError {
+    #[inline]
+    fn from(value: FenError) -> Self {
+        Self::Fen(value)
+    }
+}
+
+/// A token of the PGN import format.
+#[derive(PartialEq, Debug)]
+enum Token {
+    /// `[Name "value"]`
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Open,
+    Close,
+    /// Move number, SAN move or result
+    Symbol(String),
+}
+
+/// Splits a PGN text into tokens.
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '[' => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"') {
+                    name.push(c);
+                }
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next() != Some('"') {
+                    return Err(PgnError::Syntax(format!("tag `{name}` has no value")));
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => {
+                            return Err(PgnError::Syntax(format!("tag `{name}` is not closed")));
+                        }
+                    }
+                }
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next() != Some(']') {
+                    return Err(PgnError::Syntax(format!("tag `{name}` is not closed")));
+                }
+                tokens.push(Token::Tag(name, value));
+            }
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => return Err(PgnError::Syntax("comment is not closed".into())),
+                    }
+                }
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '$' => {
+                let mut nag = String::new();
+                while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                    nag.push(c);
+                }
+                let nag = nag
+                    .parse()
+                    .map_err(|_| PgnError::Syntax(format!("invalid NAG `${nag}`")))?;
+                tokens.push(Token::Nag(nag));
+            }
+            c => {
+                let mut symbol = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"[]{}();$".contains(*c))
+                {
+                    symbol.push(c);
+                }
+                tokens.push(Token::Symbol(symbol));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses the result token of a game.
+fn parse_result(token: &str) -> Option<Option<Outcome>> {
+    match token {
+        "1-0" => Some(Some(Outcome::Win(Color::White))),
+        "0-1" => Some(Some(Outcome::Win(Color::Black))),
+        "1/2-1/2" => Some(Some(Outcome::Draw)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
+
+/// Splits a symbol which is not a result into a move and its suffix annotation,
+/// or returns `None` if it is a move number (e.g. `12.` or `12...`) or `e.p.`.
+fn parse_symbol(symbol: &str) -> Option<(&str, Option<u8>)> {
+    let number = symbol.trim_end_matches('.');
+    // the en passant suffix is not part of the PGN export format
+    if number.is_empty() || number.chars().all(|c| c.is_ascii_digit()) || symbol == "e.p." {
+        return None;
+    }
+    let san = symbol.trim_end_matches(['!', '?']);
+    let nag = SUFFIXES
+        .iter()
+        .find(|(suffix, _)| *suffix == &symbol[san.len()..])
+        .map(|(_, nag)| *nag);
+    Some((san, nag))
+}
+
+impl Pgn {
+    /// Reads every game of a PGN text (e.g. a whole archive file).
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, PgnError> {
+        let mut games = Vec::new();
+        let mut game = Self::default();
+        // the current line and the lines it is a variation of
+        let mut lines: Vec<Vec<MoveText>> = vec![Vec::new()];
+
+        for token in tokenize(text)? {
+            let line = lines.last_mut().unwrap();
+            match token {
+                Token::Tag(name, value) => {
+                    // a game without the result token
+                    if lines.len() == 1 && !lines[0].is_empty() {
+                        games.push(game.finish(&mut lines, None)?);
+                        game = Self::default();
+                    }
+                    game.tags.insert(name, value);
+                }
+                Token::Comment(comment) => line.push(MoveText::Comment(comment)),
+                Token::Nag(nag) => line.push(MoveText::Nag(nag)),
+                Token::Open => {
+                    if !line.iter().any(|item| matches!(item, MoveText::Move(_))) {
+                        return Err(PgnError::Syntax("variation before any move".into()));
+                    }
+                    lines.push(Vec::new());
+                }
+                Token::Close => {
+                    if lines.len() == 1 {
+                        return Err(PgnError::Syntax("unmatched `)`".into()));
+                    }
+                    let variation = lines.pop().unwrap();
+                    lines
+                        .last_mut()
+                        .unwrap()
+                        .push(MoveText::Variation(variation));
+                }
+                Token::Symbol(symbol) => {
+                    if let Some(result) = parse_result(&symbol) {
+                        games.push(game.finish(&mut lines, Some(result))?);
+                        game = Self::default();
+                    } else if let Some((san, nag)) = parse_symbol(&symbol) {
+                        line.push(MoveText::Move(san.to_string()));
+                        line.extend(nag.map(MoveText::Nag));
+                    }
+                }
+            }
+        }
+
+        if !game.tags.is_empty() || lines.len() > 1 || !lines[0].is_empty() {
+            games.push(game.finish(&mut lines, None)?);
+        }
+        Ok(games)
+    }
+    /// Completes a game with the movetext read so far and its result
+    /// (from the `Result` tag if there was no result token).
+    fn finish(
+        mut self,
+        lines: &mut Vec<Vec<MoveText>>,
+        result: Option<Option<Outcome>>,
+    ) -> Result<Self, PgnError> {
+        if lines.len() > 1 {
+            return Err(PgnError::Syntax("unclosed variation".into()));
+        }
+        self.movetext = lines.pop().unwrap();
+        lines.push(Vec::new());
+        self.result = result
+            .or_else(|| self.tags.get("Result").and_then(|r| parse_result(r)))
+            .flatten();
+        Ok(self)
+    }
+    /// Reads a single game.
+    pub fn parse(text: &str) -> Result<Self, PgnError> {
+        let mut games = Self::parse_all(text)?;
+        match games.len() {
+            1 => Ok(games.pop().unwrap()),
+            0 => Err(PgnError::Syntax("no game found".into())),
+            n => Err(PgnError::Syntax(format!("expected one game, found {n}"))),
+        }
+    }
+    /// Moves (in SAN) of the main line.
+    pub fn moves(&self) -> impl Iterator<Item = &str> {
+        self.movetext.iter().filter_map(|item| match item {
+            MoveText::Move(san) => Some(san.as_str()),
+            _ => None,
+        })
+    }
+    /// The PGN of a played game, with the Seven Tag Roster filled
+    /// with today's date and unknown (`?`) values.
+    pub fn from_game(game: &Game) -> Self {
+        let mut tags = IndexMap::new();
+        for name in SEVEN_TAG_ROSTER {
+            tags.insert(name.to_string(), "?".to_string());
+        }
+        tags.insert("Date".into(), today());
+        tags.insert(
+            "Result".into(),
+            game.outcome
+                .map_or("*".into(), |outcome| outcome.to_string()),
+        );
+        if let Some(fen) = game.start_fen.as_ref().filter(|fen| *fen != START_FEN) {
+            tags.insert("SetUp".into(), "1".into());
+            tags.insert("FEN".into(), fen.clone());
+        }
+
+        Self {
+            tags,
+            movetext: game
+                .history
+                .iter()
+                .map(|played| MoveText::Move(played.san.clone()))
+                .collect(),
+            result: game.outcome,
+        }
+    }
+    /// Replays the main line of the game, validating every move.
+    ///
+    /// No move can follow a checkmate or a stalemate.
+    pub fn to_game(&self) -> Result<Game, PgnError> {
+        let mut game = match self.tags.get("FEN") {
+            Some(fen) => Game::from_fen(fen)?,
+            None => Game::default(),
+        };
+
+        for san in self.moves() {
+            let illegal = |reason: String| PgnError::IllegalMove {
+                ply: game.ply as usize + 1,
+                san: san.to_string(),
+                reason,
+            };
+            if game.board.checkmate(game.turn) || game.board.stalemate(game.turn) {
+                return Err(illegal("the game is already over".into()));
+            }
+            let (mov, promotion) = Movement::from_san(&game.board, game.turn, san)
+                .map_err(|err| illegal(err.to_string()))?;
+            game.play(&mov, promotion);
+        }
+        game.outcome = self.result;
+
+        Ok(game)
+    }
+    /// Half-move of the first move, from the `FEN` tag.
+    fn start_ply(&self) -> u32 {
+        self.tags
+            .get("FEN")
+            .and_then(|fen| Game::from_fen(fen).ok())
+            .map_or(0, |game| game.ply)
+    }
+}
+
+/// Writes a line of movetext (and its variations) as tokens.
+///
+/// `ply` is the half-move of the first move of the line.
+fn movetext_tokens(line: &[MoveText], mut ply: u32, tokens: &mut Vec<String>) {
+    let mut needs_number = true;
+    let mut last_ply = ply;
+
+    for item in line {
+        match item {
+            MoveText::Move(san) => {
+                if ply.is_multiple_of(2) {
+                    tokens.push(format!("{}.", ply / 2 + 1));
+                } else if needs_number {
+                    tokens.push(format!("{}...", ply / 2 + 1));
+                }
+                tokens.push(san.clone());
+                needs_number = false;
+                last_ply = ply;
+                ply += 1;
+            }
+            MoveText::Nag(nag) => tokens.push(format!("${nag}")),
+            MoveText::Comment(comment) => {
+                tokens.push(format!("{{{comment}}}"));
+                needs_number = true;
+            }
+            MoveText::Variation(variation) => {
+                let start = tokens.len();
+                movetext_tokens(variation, last_ply, tokens);
+                if tokens.len() == start {
+                    tokens.push("()".into());
+                } else {
+                    tokens[start].insert(0, '(');
+                    tokens.last_mut().unwrap().push(')');
+                }
+                needs_number = true;
+            }
+        }
+    }
+}
+
+impl Display for Pgn {
+    /// Writes the game in the PGN export format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = self
+            .result
+            .map_or("*".into(), |outcome| outcome.to_string());
+
+        let roster = SEVEN_TAG_ROSTER.iter().map(|name| {
+            let value = match *name {
+                "Result" => result.as_str(),
+                "Date" => self.tags.get(*name).map_or("????.??.??", String::as_str),
+                _ => self.tags.get(*name).map_or("?", String::as_str),
+            };
+            (*name, value)
+        });
+        let others = self
+            .tags
+            .iter()
+            .filter(|(name, _)| !SEVEN_TAG_ROSTER.contains(&name.as_str()))
+            .map(|(name, value)| (name.as_str(), value.as_str()));
+        for (name, value) in roster.chain(others) {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        writeln!(f)?;
+
+        let mut tokens = Vec::new();
+        movetext_tokens(&self.movetext, self.start_ply(), &mut tokens);
+        tokens.push(result);
+
+        let mut width = 0;
+        for token in tokens {
+            if width > 0 && width + 1 + token.len() > LINE_WIDTH {
+                writeln!(f)?;
+                width = 0;
+            }
+            if width > 0 {
+                write!(f, " ")?;
+                width += 1;
+            }
+            write!(f, "{token}")?;
+            width += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+impl Game {
+    /// Constructor of `Game` from a PGN game, replaying its main line.
+    #[inline]
+    pub fn from_pgn(pgn: &str) -> Result<Self, PgnError> {
+        Pgn::parse(pgn)?.to_game()
+    }
+    /// The game (with its history) in Portable Game Notation.
+    #[inline]
+    pub fn to_pgn(&self) -> String {
+        Pgn::from_game(self).to_string()
+    }
+}
+
+/// Today's date in the PGN format (`YYYY.MM.DD`).
+fn today() -> String {
+    let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return "????.??.??".into();
+    };
+    // days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let days = (elapsed.as_secs() / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}.{month:02}.{day:02}")
+}
@@ -0,0 +1,185 @@
+//! [Standard Algebraic Notation](https://en.wikipedia.org/wiki/Algebraic_notation_(chess))
+//! of a single move.
+
+use {
+    crate::{chessboard::Board, geomath::Point, types::*},
+    std::error::Error,
+};
+
+impl Movement {
+    /// Renders a legal move in SAN (e.g. `Nbd7`, `O-O-O`, `e8=Q+`, `Qh4#`).
+    ///
+    /// `promotion` is the letter of the piece a pawn promotes to.
+    ///
+    /// > En passant is written as a normal pawn capture (`exd6`),
+    /// > without the optional `e.p.` suffix, as required by PGN.
+    ///
+    /// The move is done and taken back on `board` to find the check suffix.
+    pub fn to_san(&self, board: &mut Board, promotion: Option<char>) -> String {
+        let piece = board[self.from].as_ref().unwrap();
+        let (color, symbol, pawn) = (piece.color(), piece.symbol(), piece.as_any().is::<Pawn>());
+
+        let mut san = match self.special {
+            Some(SpecialMove::ShortCastle) => "O-O".to_string(),
+            Some(SpecialMove::LongCastle) => "O-O-O".to_string(),
+            _ => {
+                let mut san = String::new();
+                let capture =
+                    board[self.to].is_some() || self.special == Some(SpecialMove::PawnEat);
+
+                if pawn {
+                    if capture {
+                        san.push(file(self.from));
+                    }
+                } else {
+                    san.push(symbol);
+                    san.push_str(&disambiguation(board, self));
+                }
+                if capture {
+                    san.push('x');
+                }
+                san.push_str(&self.to.algebraic());
+                if let Some(c) = promotion {
+                    san.push('=');
+                    san.push(c.to_ascii_uppercase());
+                }
+                san
+            }
+        };
+
+        let undo = board.make_move_promoting(self, promotion);
+        if board.check(color.opposite()).is_some() {
+            let mated = !board
+                .all_moves(color.opposite())
+                .into_iter()
+                .any(|mov| board.is_legal(&mov));
+            san.push(if mated { '#' } else { '+' });
+        }
+        board.unmake_move(undo);
+        san
+    }
+
+    /// Parses a move of `color` in SAN, returning it with the letter
+    /// of the piece a pawn promotes to.
+    ///
+    /// Check and annotation suffixes (`+`, `#`, `!`, `?`) are ignored,
+    /// castles can be written with zeros (`0-0`) and en passant can have the `e.p.` suffix.
+    /// Only legal moves are accepted.
+    pub fn from_san(
+        board: &Board,
+        color: Color,
+        san: &str,
+    ) -> Result<(Self, Option<char>), Box<dyn Error>> {
+        let suffixes = ['+', '#', '!', '?'];
+        let san = san.trim().trim_end_matches(suffixes);
+        let (san, en_passant) = match san.strip_suffix("e.p.") {
+            Some(san) => (san.trim_end().trim_end_matches(suffixes), true),
+            None => (san, false),
+        };
+
+        let castle = match san {
+            "O-O" | "0-0" => Some(SpecialMove::ShortCastle),
+            "O-O-O" | "0-0-0" => Some(SpecialMove::LongCastle),
+            _ => None,
+        };
+        if let Some(castle) = castle {
+            return board
+                .legal_moves(color)
+                .into_iter()
+                .find(|mov| mov.special.as_ref() == Some(&castle))
+                .map(|mov| (mov, None))
+                .ok_or_else(|| format!("illegal move `{san}`").into());
+        }
+
+        let (body, promotion) = match san.split_once('=') {
+            Some((body, piece)) => {
+                let mut chars = piece.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c @ ('N' | 'B' | 'R' | 'Q')), None) => (body, Some(c)),
+                    _ => return Err(format!("invalid promotion in `{san}`").into()),
+                }
+            }
+            None => (san, None),
+        };
+
+        let (symbol, body) = match body.chars().next() {
+            Some(c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (c, &body[1..]),
+            _ => ('P', body),
+        };
+        let body: String = body.chars().filter(|c| *c != 'x').collect();
+        if body.len() < 2 || !body.is_ascii() {
+            return Err(format!("invalid move `{san}`").into());
+        }
+        let (hint, to) = body.split_at(body.len() - 2);
+        let to = Point::try_from(to)?;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in hint.chars() {
+            match c {
+                'a'..='h' => from_file = Some(c as isize - 'a' as isize),
+                '1'..='8' => from_rank = Some(c as isize - '1' as isize),
+                _ => return Err(format!("invalid move `{san}`").into()),
+            }
+        }
+
+        let candidates: Vec<Self> = board
+            .legal_moves(color)
+            .into_iter()
+            .filter(|mov| {
+                mov.to == to
+                    && board[mov.from].as_ref().unwrap().symbol() == symbol
+                    && from_file.is_none_or(|x| mov.from.x == x)
+                    && from_rank.is_none_or(|y| mov.from.y == y)
+                    && !mov.is_castle()
+            })
+            .collect();
+
+        let mov = match candidates.as_slice() {
+            [] => return Err(format!("illegal move `{san}`").into()),
+            [mov] => mov,
+            _ => return Err(format!("ambiguous move `{san}`").into()),
+        };
+        if en_passant && !(mov.special == Some(SpecialMove::PawnEat) && board[to].is_none()) {
+            return Err(format!("`{san}` is not an en passant").into());
+        }
+        match (board.is_promoting(mov), promotion) {
+            (true, None) => Err(format!("missing promotion in `{san}`").into()),
+            (false, Some(_)) => Err(format!("`{san}` can't promote").into()),
+            _ => Ok((mov.clone(), promotion)),
+        }
+    }
+}
+
+/// File letter of a square (`a`-`h`).
+#[inline]
+fn file(point: Point) -> char {
+    (b'a' + point.x as u8) as char
+}
+
+/// File and/or rank needed to tell a piece's move apart from the other
+/// legal moves of pieces of the same kind to the same square.
+fn disambiguation(board: &mut Board, mov: &Movement) -> String {
+    let piece = board[mov.from].as_ref().unwrap();
+    let mut others: Vec<Movement> = board
+        .all_moves(piece.color())
+        .into_iter()
+        .filter(|other| {
+            other.to == mov.to
+                && other.from != mov.from
+                && board[other.from].as_ref().unwrap().symbol() == piece.symbol()
+        })
+        .collect();
+    others.retain(|other| board.is_legal(other));
+    let others: Vec<Point> = others.into_iter().map(|other| other.from).collect();
+
+    if others.is_empty() {
+        String::new()
+    } else if others.iter().all(|from| from.x != mov.from.x) {
+        file(mov.from).to_string()
+    } else if others.iter().all(|from| from.y != mov.from.y) {
+        (mov.from.y + 1).to_string()
+    } else {
+        mov.from.algebraic()
+    }
+}
@@ -0,0 +1,508 @@
+use {crate::prelude::*, std::error::Error};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+// FEN export
+#[test]
+fn start_to_fen() {
+    assert_eq!(Game::default().to_fen(), START_FEN);
+}
+#[test]
+fn start_from_fen() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen(START_FEN)?;
+    assert_eq!(game.turn, Color::White);
+    assert_eq!(game.move_count, 0);
+    assert_eq!(game.ply, 0);
+    assert_eq!(
+        game.board.filtered_move_set(Point::new(4, 1)),
+        Board::default().filtered_move_set(Point::new(4, 1))
+    );
+    assert!(
+        game.board[Point::new(4, 0)]
+            .as_ref()
+            .unwrap()
+            .is_state(PieceState::NotYet.into())
+    );
+    Ok(())
+}
+#[test]
+fn round_trip() -> Result<(), Box<dyn Error>> {
+    for fen in [
+        KIWIPETE,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 b - - 10 10",
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 3",
+    ] {
+        assert_eq!(Game::from_fen(fen)?.to_fen(), fen);
+    }
+    Ok(())
+}
+#[test]
+fn chess960_castling_rights() -> Result<(), Box<dyn Error>> {
+    // X-FEN writes the outermost Rooks as KQkq, Shredder-FEN always writes the files
+    let game = Game::chess960(0);
+    assert_eq!(
+        game.to_fen(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    );
+    assert_eq!(
+        game.to_shredder_fen(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+    );
+    assert_eq!(
+        Game::from_fen(&game.to_shredder_fen())?.to_fen(),
+        game.to_fen()
+    );
+    assert_eq!(
+        Game::default().to_shredder_fen(),
+        START_FEN.replace("KQkq", "HAha")
+    );
+
+    // an inner Rook is written as its file in X-FEN too
+    let game = Game::from_fen("1r2k3/8/8/8/8/8/8/RR3K1R w HBb - 0 1")?;
+    assert_eq!(game.board.fen_castling(), "KBq");
+    assert_eq!(game.board.shredder_fen_castling(), "HBb");
+    assert_eq!(
+        game.board
+            .castling_rook(Color::White, &SpecialMove::LongCastle),
+        Some(Point::try_from("b1")?)
+    );
+    // the outermost Rook
+    let game = Game::from_fen("1r2k3/8/8/8/8/8/8/RR3K1R w Q - 0 1")?;
+    assert_eq!(
+        game.board
+            .castling_rook(Color::White, &SpecialMove::LongCastle),
+        Some(Point::try_from("a1")?)
+    );
+    Ok(())
+}
+#[test]
+fn after_moves() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    for (from, to) in [("e2", "e4"), ("g8", "f6"), ("e1", "e2")] {
+        let (from, to) = (Point::try_from(from)?, Point::try_from(to)?);
+        let mov = game
+            .board
+            .filtered_move_set(from)
+            .into_iter()
+            .find(|mov| mov.to == to)
+            .unwrap();
+        game.fifty_moves(&mov);
+        game.board.do_move(&mov, None);
+        game.turn = game.turn.opposite();
+        game.ply += 1;
+
+        if to == Point::new(4, 3) {
+            assert_eq!(
+                game.to_fen(),
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+            );
+        }
+    }
+    // the en passant window is closed and white can't castle anymore
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2"
+    );
+    Ok(())
+}
+#[test]
+fn optional_clocks() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 b - -")?;
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(game.ply, 1);
+    Ok(())
+}
+#[test]
+fn large_halfmove_clock() -> Result<(), Box<dyn Error>> {
+    for (clock, after) in [(255, 256), (u16::MAX, u16::MAX)] {
+        let mut game = Game::from_fen(&format!("4k3/8/8/8/8/8/8/R3K3 w - - {clock} 200"))?;
+        assert_eq!(game.move_count, clock);
+
+        let (mov, promotion) = Movement::from_uci(&game.board, game.turn, "a1a2")?;
+        game.play(&mov, promotion);
+        assert_eq!(game.move_count, after);
+        assert_eq!(
+            game.to_fen(),
+            format!("4k3/8/8/8/8/8/R7/4K3 b - - {after} 200")
+        );
+    }
+    Ok(())
+}
+
+// FEN import
+#[test]
+fn castling_states() -> Result<(), Box<dyn Error>> {
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1")?;
+    let not_moved = |pos: &str| {
+        board[Point::try_from(pos).unwrap()]
+            .as_ref()
+            .unwrap()
+            .is_state(PieceState::NotYet.into())
+    };
+    assert!(not_moved("e1") && not_moved("h1") && not_moved("e8") && not_moved("a8"));
+    assert!(!not_moved("a1") && !not_moved("h8"));
+    Ok(())
+}
+#[test]
+fn en_passant_state() -> Result<(), Box<dyn Error>> {
+    let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 3")?;
+    let from = Point::try_from("e5")?;
+    assert!(
+        board
+            .filtered_move_set(from)
+            .into_iter()
+            .any(|mov| mov.to == Point::try_from("d6").unwrap())
+    );
+    Ok(())
+}
+#[test]
+fn pawn_states() -> Result<(), Box<dyn Error>> {
+    let board = Board::from_fen("4k3/8/8/8/8/4P3/3P4/4K3 w - - 0 1")?;
+    assert_eq!(board.filtered_move_set(Point::try_from("d2")?).len(), 2);
+    assert_eq!(board.filtered_move_set(Point::try_from("e3")?).len(), 1);
+    Ok(())
+}
+
+// Errors
+fn field_of(fen: &str) -> FenField {
+    Game::from_fen(fen).unwrap_err().field
+}
+#[test]
+fn placement_errors() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/7/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQXBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQQBNR w kq - 0 1",
+        "rnbqkbnP/pppppppp/8/8/8/8/PPPPPPP1/RNBQKBNR w KQkq - 0 1",
+        "",
+    ] {
+        assert_eq!(field_of(fen), FenField::Placement, "{fen}");
+    }
+}
+#[test]
+fn field_errors() {
+    let cases = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+            FenField::ActiveColor,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            FenField::ActiveColor,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1",
+            FenField::Castling,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KK - 0 1",
+            FenField::Castling,
+        ),
+        ("4k3/8/8/8/8/8/8/4K3 w K - 0 1", FenField::Castling),
+        ("4k3/8/8/8/8/8/8/R3K2R w E - 0 1", FenField::Castling),
+        ("4k3/8/8/8/8/8/8/R3K2R w G - 0 1", FenField::Castling),
+        ("4k3/8/8/8/8/8/8/R3K2R w HK - 0 1", FenField::Castling),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+            FenField::EnPassant,
+        ),
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d3 0 1", FenField::EnPassant),
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - z9 0 1", FenField::EnPassant),
+        ("4k3/8/8/8/8/8/8/4K3 w - - -1 1", FenField::HalfmoveClock),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 65536 1", FenField::HalfmoveClock),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 0", FenField::FullmoveNumber),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra",
+            FenField::FullmoveNumber,
+        ),
+    ];
+    for (fen, field) in cases {
+        assert_eq!(field_of(fen), field, "{fen}");
+    }
+}
+#[test]
+fn error_message() {
+    let err = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 zero").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid FEN fullmove number: `zero` is not a positive number"
+    );
+}
+
+// SAN
+fn san_of(fen: &str, from: &str, to: &str, promotion: Option<char>) -> String {
+    let mut board = Board::from_fen(fen).unwrap();
+    let (from, to) = (Point::try_from(from).unwrap(), Point::try_from(to).unwrap());
+    let mov = board
+        .filtered_move_set(from)
+        .into_iter()
+        .find(|mov| mov.to == to)
+        .unwrap();
+    mov.to_san(&mut board, promotion)
+}
+#[test]
+fn san_rendering() {
+    let knights = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(san_of(knights, "b1", "d2", None), "Nbd2");
+    assert_eq!(san_of(knights, "f1", "d2", None), "Nfd2");
+    assert_eq!(san_of(knights, "f1", "g3", None), "Ng3");
+
+    let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(san_of(rooks, "a1", "a3", None), "R1a3");
+    assert_eq!(san_of(rooks, "a5", "a3", None), "R5a3");
+
+    let queens = "4k3/8/8/8/Q1Q5/8/Q7/4K3 w - - 0 1";
+    assert_eq!(san_of(queens, "a4", "b3", None), "Qa4b3");
+
+    assert_eq!(
+        san_of("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7", "a8", Some('Q')),
+        "a8=Q+"
+    );
+    assert_eq!(san_of(KIWIPETE, "e1", "g1", None), "O-O");
+    assert_eq!(san_of(KIWIPETE, "e1", "c1", None), "O-O-O");
+    assert_eq!(san_of(KIWIPETE, "e5", "f7", None), "Nxf7");
+    assert_eq!(san_of(KIWIPETE, "d5", "e6", None), "dxe6");
+    assert_eq!(
+        san_of(
+            "rnbqkbnr/pppp1ppp/4p3/8/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2",
+            "d8",
+            "h4",
+            None
+        ),
+        "Qh4#"
+    );
+}
+#[test]
+fn san_parsing() -> Result<(), Box<dyn Error>> {
+    let board = Board::from_fen(KIWIPETE)?;
+    let (mov, promotion) = Movement::from_san(&board, Color::White, "O-O-O")?;
+    assert_eq!(mov.special, Some(SpecialMove::LongCastle));
+    assert_eq!(promotion, None);
+
+    let (mov, _) = Movement::from_san(&board, Color::White, "Nxf7!")?;
+    assert_eq!(mov.from, Point::try_from("e5")?);
+
+    let (mov, _) = Movement::from_san(&board, Color::Black, "b3")?;
+    assert_eq!(mov.from, Point::try_from("b4")?);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1")?;
+    assert!(Movement::from_san(&board, Color::White, "Nd2").is_err()); // ambiguous
+    assert_eq!(
+        Movement::from_san(&board, Color::White, "Nfd2")?.0.from,
+        Point::try_from("f1")?
+    );
+    assert!(Movement::from_san(&board, Color::White, "Nc4").is_err()); // illegal
+
+    let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(
+        Movement::from_san(&board, Color::White, "a8=N")?.1,
+        Some('N')
+    );
+    assert!(Movement::from_san(&board, Color::White, "a8").is_err()); // no promotion
+    Ok(())
+}
+
+#[test]
+fn san_en_passant() -> Result<(), Box<dyn Error>> {
+    let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 3")?;
+    let (mov, _) = Movement::from_san(&board, Color::White, "exd6 e.p.")?;
+    assert_eq!(mov.special, Some(SpecialMove::PawnEat));
+    assert_eq!(mov.to_san(&mut board, None), "exd6");
+
+    let board = Board::from_fen("4k3/8/2p5/3P4/8/8/8/4K3 w - - 0 3")?;
+    assert!(Movement::from_san(&board, Color::White, "dxc6").is_ok());
+    assert!(Movement::from_san(&board, Color::White, "dxc6 e.p.").is_err());
+    Ok(())
+}
+
+// UCI
+#[test]
+fn uci_rendering() -> Result<(), Box<dyn Error>> {
+    let board = Board::from_fen(KIWIPETE)?;
+    let (mov, _) = Movement::from_san(&board, Color::White, "O-O")?;
+    assert_eq!(mov.to_uci(None), "e1g1");
+
+    let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1")?;
+    let (mov, promotion) = Movement::from_san(&board, Color::White, "a8=N")?;
+    assert_eq!(mov.to_uci(promotion), "a7a8n");
+    Ok(())
+}
+
+#[test]
+fn uci_parsing() -> Result<(), Box<dyn Error>> {
+    let board = Board::default();
+    let (mov, promotion) = Movement::from_uci(&board, Color::White, "e2e4")?;
+    assert_eq!(mov.special, Some(SpecialMove::DoublePawn));
+    assert_eq!(promotion, None);
+    assert_eq!(
+        Movement::from_uci(&board, Color::White, "G1-F3")?.0.to,
+        Point::try_from("f3")?
+    );
+    assert!(Movement::from_uci(&board, Color::White, "e2e5").is_err()); // illegal
+    assert!(Movement::from_uci(&board, Color::White, "e7e5").is_err()); // not white's
+    assert!(Movement::from_uci(&board, Color::White, "e2").is_err());
+
+    let board = Board::from_fen(KIWIPETE)?;
+    let (mov, _) = Movement::from_uci(&board, Color::White, "e1c1")?;
+    assert_eq!(mov.special, Some(SpecialMove::LongCastle));
+
+    assert_eq!(
+        Movement::from_uci(&board, Color::White, "e1h1")?.0.special,
+        Some(SpecialMove::ShortCastle)
+    );
+    // in Chess960 `f1g1` is a move of the King, the castle is written as taking the Rook
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/5K1R w H - 0 1")?;
+    assert_eq!(
+        Movement::from_uci(&board, Color::White, "f1g1")?.0.special,
+        None
+    );
+    assert_eq!(
+        Movement::from_uci(&board, Color::White, "f1h1")?.0,
+        Movement::from_san(&board, Color::White, "O-O")?.0
+    );
+
+    let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(
+        Movement::from_uci(&board, Color::White, "a7a8r")?.1,
+        Some('R')
+    );
+    assert_eq!(
+        Movement::from_uci(&board, Color::White, "a7a8=Q")?.1,
+        Some('Q')
+    );
+    assert!(Movement::from_uci(&board, Color::White, "a7a8").is_err()); // no promotion
+    assert!(Movement::from_uci(&board, Color::White, "a7a8k").is_err());
+    assert!(Movement::from_uci(&board, Color::White, "e1e2q").is_err());
+    Ok(())
+}
+
+// PGN
+const ANNOTATED: &str = r#"[Event "Club \"blitz\""]
+[Site "?"]
+[White "Alice"]
+[Black "Bob"]
+[Result "0-1"]
+[Annotator "Carol"]
+
+{Opening} 1. e4 e5 2. Nf3 $1 Nc6 (2... d6 3. d4 (3. Bc4) 3... exd4) 3. Bc4!?
+; line comment
+Nd4? 4. Nxe5 Qg5 5. Nxf7 Qxg2 6. Rf1 Qxe4+ 7. Be2 Nf3# 0-1
+"#;
+
+#[test]
+fn pgn_parsing() -> Result<(), Box<dyn Error>> {
+    let pgn = Pgn::parse(ANNOTATED)?;
+    assert_eq!(pgn.tags["Event"], "Club \"blitz\"");
+    assert_eq!(pgn.tags["Annotator"], "Carol");
+    assert_eq!(pgn.result, Some(Outcome::Win(Color::Black)));
+    assert_eq!(pgn.moves().count(), 14);
+    assert_eq!(pgn.movetext[0], MoveText::Comment("Opening".into()));
+    assert_eq!(pgn.movetext[4], MoveText::Nag(1));
+    assert_eq!(
+        pgn.movetext[6],
+        MoveText::Variation(vec![
+            MoveText::Move("d6".into()),
+            MoveText::Move("d4".into()),
+            MoveText::Variation(vec![MoveText::Move("Bc4".into())]),
+            MoveText::Move("exd4".into()),
+        ])
+    );
+    assert_eq!(pgn.movetext[8], MoveText::Nag(5));
+    assert_eq!(pgn.movetext[9], MoveText::Comment("line comment".into()));
+    Ok(())
+}
+#[test]
+fn pgn_replay() -> Result<(), Box<dyn Error>> {
+    let game = Game::from_pgn(ANNOTATED)?;
+    assert_eq!(
+        game.to_fen(),
+        "r1b1kbnr/pppp1Npp/8/8/4q3/5n2/PPPPBP1P/RNBQKR2 w Qkq - 2 8"
+    );
+    assert_eq!(game.history.len(), 14);
+    assert_eq!(game.history[13].san, "Nf3#");
+    assert_eq!(game.outcome, Some(Outcome::Win(Color::Black)));
+    Ok(())
+}
+#[test]
+fn pgn_writing() -> Result<(), Box<dyn Error>> {
+    let pgn = Pgn::parse(ANNOTATED)?;
+    let expected = r#"[Event "Club \"blitz\""]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "Alice"]
+[Black "Bob"]
+[Result "0-1"]
+[Annotator "Carol"]
+
+{Opening} 1. e4 e5 2. Nf3 $1 Nc6 (2... d6 3. d4 (3. Bc4) 3... exd4) 3. Bc4 $5
+{line comment} 3... Nd4 $2 4. Nxe5 Qg5 5. Nxf7 Qxg2 6. Rf1 Qxe4+ 7. Be2 Nf3# 0-1
+"#;
+    assert_eq!(pgn.to_string(), expected);
+    assert_eq!(Pgn::parse(expected)?, Pgn::parse(&pgn.to_string())?);
+    Ok(())
+}
+#[test]
+fn game_to_pgn() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30")?;
+    for san in ["Kd7", "e4", "Ke6"] {
+        let (mov, promotion) = Movement::from_san(&game.board, game.turn, san)?;
+        game.play(&mov, promotion);
+    }
+    let pgn = Pgn::parse(&game.to_pgn())?;
+    assert_eq!(pgn.tags["SetUp"], "1");
+    assert_eq!(pgn.tags["FEN"], "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30");
+    assert_eq!(pgn.result, None);
+    assert!(pgn.to_string().contains("\n30... Kd7 31. e4 Ke6 *\n"));
+    assert_eq!(pgn.to_game()?.to_fen(), game.to_fen());
+    Ok(())
+}
+#[test]
+fn pgn_illegal_move() {
+    let err = Game::from_pgn("1. e4 e5 2. Ke3 Nc6 *").unwrap_err();
+    let PgnError::IllegalMove { ply, san, .. } = err else {
+        panic!("{err}");
+    };
+    assert_eq!((ply, san.as_str()), (3, "Ke3"));
+
+    // the plies go on from the position of the FEN tag
+    let err = Game::from_pgn(
+        "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n\n30... Kd7 31. Kd2 Kc6 32. e5 *",
+    )
+    .unwrap_err();
+    let PgnError::IllegalMove { ply, san, .. } = err else {
+        panic!("{err}");
+    };
+    assert_eq!((ply, san.as_str()), (63, "e5"));
+
+    // no move after a checkmate
+    let err = Game::from_pgn("1. f3 e5 2. g4 Qh4# 3. Kf2 *").unwrap_err();
+    let PgnError::IllegalMove { ply, reason, .. } = err else {
+        panic!("{err}");
+    };
+    assert_eq!((ply, reason.as_str()), (5, "the game is already over"));
+}
+#[test]
+fn pgn_archive() -> Result<(), Box<dyn Error>> {
+    let games =
+        Pgn::parse_all("[Event \"1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n[Event \"2\"]\n\n1. d4 *\n")?;
+    assert_eq!(games.len(), 2);
+    assert_eq!(
+        games[0].to_game()?.outcome,
+        Some(Outcome::Win(Color::Black))
+    );
+    assert_eq!(games[1].tags["Event"], "2");
+    assert!(Pgn::parse("1. e4 (1. d4 *").is_err());
+    assert!(Pgn::parse("1. e4 ) *").is_err());
+    assert!(matches!(
+        Pgn::parse("1. e4 {unclosed comment *"),
+        Err(PgnError::Syntax(_))
+    ));
+    Ok(())
+}
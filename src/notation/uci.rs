@@ -0,0 +1,85 @@
+//! Long algebraic notation of a single move, in the coordinate form used by
+//! [UCI](https://en.wikipedia.org/wiki/Universal_Chess_Interface) (`e2e4`, `e7e8q`).
+
+use {
+    crate::{chessboard::Board, geomath::Point, types::*},
+    std::error::Error,
+};
+
+impl Movement {
+    /// Renders a move as its starting and arriving squares,
+    /// followed by the lowercase letter of the piece a pawn promotes to (e.g. `e7e8q`).
+    ///
+    /// Castles are written as the move of the King (`e1g1`): in Chess960 this may be
+    /// the same as a normal move, which [`Movement::from_uci`] tells apart.
+    pub fn to_uci(&self, promotion: Option<char>) -> String {
+        let mut uci = self.from.algebraic() + &self.to.algebraic();
+        if let Some(c) = promotion {
+            uci.push(c.to_ascii_lowercase());
+        }
+        uci
+    }
+
+    /// Parses a move of `color` written as its starting and arriving squares
+    /// (`e2e4`, `E2-E4`, `e4xd5`), returning it with the letter of the piece a pawn promotes to
+    /// (`e7e8q` or `e7e8=Q`).
+    ///
+    /// Only legal moves are accepted.
+    /// A castle can also be written as the King taking its own Rook (`e1h1`),
+    /// as in the Chess960 mode of UCI.
+    pub fn from_uci(
+        board: &Board,
+        color: Color,
+        uci: &str,
+    ) -> Result<(Self, Option<char>), Box<dyn Error>> {
+        let uci = uci.trim();
+        let chars: Vec<char> = uci
+            .chars()
+            .filter(|c| !matches!(c, '-' | 'x' | '='))
+            .collect();
+        if !(4..=5).contains(&chars.len()) {
+            return Err(format!("invalid move `{uci}`").into());
+        }
+
+        let from = Point::try_from(String::from_iter(&chars[..2]).as_str())?;
+        let to = Point::try_from(String::from_iter(&chars[2..4]).as_str())?;
+        let promotion = match chars.get(4).map(char::to_ascii_uppercase) {
+            Some(c @ ('N' | 'B' | 'R' | 'Q')) => Some(c),
+            Some(_) => return Err(format!("invalid promotion in `{uci}`").into()),
+            None => None,
+        };
+
+        if board[from]
+            .as_ref()
+            .is_none_or(|piece| piece.color() != color)
+        {
+            return Err(format!("no piece to move in `{uci}`").into());
+        }
+        let mut board = board.clone();
+        let mut moves: Vec<Self> = board.filtered_move_set(from).into_iter().collect();
+        moves.retain(|mov| board.is_legal(mov));
+        let rook = |mov: &Self| {
+            mov.special
+                .as_ref()
+                .and_then(|castle| board.castling_rook(color, castle))
+        };
+        // in Chess960 a castle can also be written as the King taking its own Rook,
+        // which is the only way when a normal move of the King has the same squares
+        let mov = moves
+            .iter()
+            .find(|mov| mov.to == to && !mov.is_castle())
+            .or_else(|| {
+                moves
+                    .iter()
+                    .find(|mov| mov.is_castle() && (mov.to == to || rook(mov) == Some(to)))
+            })
+            .cloned()
+            .ok_or_else(|| format!("illegal move `{uci}`"))?;
+
+        match (board.is_promoting(&mov), promotion) {
+            (true, None) => Err(format!("missing promotion in `{uci}`").into()),
+            (false, Some(_)) => Err(format!("`{uci}` can't promote").into()),
+            _ => Ok((mov, promotion)),
+        }
+    }
+}
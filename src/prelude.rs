@@ -2,7 +2,12 @@
 
 pub use crate::{
     chessboard::*,
-    game::Game,
+    engine::{Engine, Limits, SearchResult},
+    game::{
+        Game, Outcome, Player,
+        clock::{Bonus, Clock, Stage, TimeControl},
+    },
     geomath::{rotation::*, *},
+    notation::*,
     types::*,
 };
@@ -0,0 +1,56 @@
+//! Text protocols spoken over stdin/stdout by chess GUIs and tournament managers,
+//! to use the [`Engine`](crate::engine::Engine) outside of the terminal game.
+
+#[cfg(test)]
+mod tests;
+pub mod uci;
+pub mod xboard;
+
+use std::{
+    io::{self, BufRead},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::Duration,
+};
+
+/// Depth searched when only the time (or the GUI) ends the search
+pub(crate) const MAX_DEPTH: usize = 64;
+/// Moves the remaining time is split into, when the GUI doesn't tell
+pub(crate) const MOVES_TO_GO: u32 = 30;
+/// Time kept on the clock to send the move
+const MARGIN: Duration = Duration::from_millis(50);
+
+/// Time to think about a move with `time_left` on the clock:
+/// an equal share for each of the next `moves_to_go` moves, plus half the increment.
+pub(crate) fn time_for_move(
+    time_left: Duration,
+    increment: Duration,
+    moves_to_go: u32,
+) -> Duration {
+    let time = time_left / moves_to_go.max(1) + increment / 2;
+    time.min(time_left.saturating_sub(MARGIN))
+}
+
+/// Reads the lines of stdin on another thread, so they keep coming while a search runs.
+///
+/// The lines for which `interrupt` is `true` set the `stop` flag of the search as soon as
+/// they are read. The flag is cleared on the thread running the commands, before a search starts.
+pub(crate) fn read_lines(stop: Arc<AtomicBool>, interrupt: fn(&str) -> bool) -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if interrupt(&line) {
+                stop.store(true, Ordering::Relaxed);
+            }
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
@@ -0,0 +1,376 @@
+use {
+    crate::{
+        engine::Limits,
+        prelude::*,
+        protocol::{uci::Uci, xboard::XBoard},
+    },
+    std::{
+        error::Error,
+        mem,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        thread,
+        time::Duration,
+    },
+};
+
+/// Answers of `uci` to the commands, one per line, once the search is over
+fn uci_session(uci: &mut Uci<Vec<u8>>, commands: &[&str]) -> Result<String, Box<dyn Error>> {
+    for command in commands {
+        uci.command(command)?;
+    }
+    uci.wait()?;
+    let out = mem::take(&mut *uci.output().lock().unwrap());
+    Ok(String::from_utf8(out)?)
+}
+
+/// Answers of `xboard` to the commands, one per line
+fn xboard_session(xboard: &mut XBoard, commands: &[&str]) -> Result<String, Box<dyn Error>> {
+    let mut out = vec![];
+    for command in commands {
+        xboard.command(command, &mut out)?;
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn uci_handshake() -> Result<(), Box<dyn Error>> {
+    let out = uci_session(&mut Uci::default(), &["uci", "isready"])?;
+    let lines: Vec<_> = out.lines().collect();
+    assert!(lines[0].starts_with("id name rusty-chess"));
+    assert_eq!(lines[lines.len() - 2..], ["uciok", "readyok"]);
+    Ok(())
+}
+
+#[test]
+fn uci_quit() -> Result<(), Box<dyn Error>> {
+    let mut uci = Uci::<Vec<u8>>::default();
+    assert!(uci.command("isready")?);
+    assert!(!uci.command("quit")?);
+    Ok(())
+}
+
+#[test]
+fn uci_position() -> Result<(), Box<dyn Error>> {
+    let mut uci = Uci::default();
+    uci_session(&mut uci, &["position startpos moves e2e4 c7c5 g1f3"])?;
+    assert_eq!(
+        uci.game().to_fen(),
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+
+    let fen = "8/P7/8/8/8/8/k7/4K3 w - - 0 1";
+    uci_session(&mut uci, &[&format!("position fen {fen} moves a7a8q a2b2")])?;
+    assert_eq!(uci.game().to_fen(), "Q7/8/8/8/8/8/1k6/4K3 w - - 1 2");
+
+    uci_session(&mut uci, &["ucinewgame"])?;
+    assert_eq!(uci.game().to_fen(), START_FEN);
+    Ok(())
+}
+
+#[test]
+fn uci_invalid_position() -> Result<(), Box<dyn Error>> {
+    let mut uci = Uci::default();
+    let out = uci_session(&mut uci, &["position startpos moves e2e5"])?;
+    assert!(out.starts_with("info string"));
+    // the position is left untouched
+    assert_eq!(uci.game().to_fen(), START_FEN);
+    Ok(())
+}
+
+#[test]
+fn uci_go() -> Result<(), Box<dyn Error>> {
+    let mut uci = Uci::default();
+    let out = uci_session(
+        &mut uci,
+        &[
+            "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "go depth 3",
+        ],
+    )?;
+    let lines: Vec<_> = out.lines().collect();
+    // the quiescence search sees the mate at depth 1
+    assert!(lines[0].starts_with("info depth 1 score mate 1"));
+    assert!(lines[0].ends_with("pv a1a8"));
+    assert_eq!(lines[1..], ["bestmove a1a8"]);
+
+    // the Knight takes the Queen
+    let out = uci_session(
+        &mut uci,
+        &[
+            "position fen 4k3/8/8/3q4/8/2N5/8/4K3 w - - 0 1",
+            "go depth 2",
+        ],
+    )?;
+    let lines: Vec<_> = out.lines().collect();
+    let score: i32 = lines[1]
+        .strip_prefix("info depth 2 score cp ")
+        .and_then(|info| info.split_whitespace().next())
+        .ok_or(lines[1])?
+        .parse()?;
+    // a Knight up, give or take where the pieces stand
+    assert!((score - 300).abs() < 100, "{score}");
+    assert_eq!(lines[2..], ["bestmove c3d5"]);
+
+    // stalemate
+    let out = uci_session(
+        &mut uci,
+        &["position fen 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", "go depth 1"],
+    )?;
+    assert_eq!(out, "bestmove 0000\n");
+    Ok(())
+}
+
+#[test]
+fn uci_infinite() -> Result<(), Box<dyn Error>> {
+    let take_output =
+        |uci: &Uci<Vec<u8>>| String::from_utf8(mem::take(&mut *uci.output().lock().unwrap()));
+
+    // the mate is found at once, but the bestmove waits for `stop`
+    let mut uci = Uci::default();
+    uci.command("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    uci.command("go infinite")?;
+    thread::sleep(Duration::from_millis(100));
+    uci.command("isready")?;
+    let out = take_output(&uci)?;
+    assert!(out.contains("readyok\n"));
+    assert!(!out.contains("bestmove"));
+    let out = uci_session(&mut uci, &["stop"])?;
+    assert!(out.ends_with("bestmove a1a8\n"));
+
+    // `isready` is answered during the search, which runs until `stop`
+    let mut uci = Uci::default();
+    uci.command("position startpos")?;
+    uci.command("go infinite")?;
+    uci.command("isready")?;
+    assert!(take_output(&uci)?.contains("readyok\n"));
+    thread::sleep(Duration::from_millis(100));
+    let out = uci_session(&mut uci, &["stop"])?;
+    assert!(out.lines().last().unwrap().starts_with("bestmove "));
+
+    // a `stop` read before `go` doesn't stop the next search
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut uci = Uci::new(Arc::clone(&stop), Vec::new());
+    uci.command("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    stop.store(true, Ordering::Relaxed);
+    uci.command("go infinite")?;
+    thread::sleep(Duration::from_millis(100));
+    uci.command("isready")?;
+    assert!(!take_output(&uci)?.contains("bestmove"));
+    let out = uci_session(&mut uci, &["stop"])?;
+    assert!(out.ends_with("bestmove a1a8\n"));
+    Ok(())
+}
+
+#[test]
+fn uci_limits() -> Result<(), Box<dyn Error>> {
+    let mut uci = Uci::default();
+    assert_eq!(
+        uci.limits("depth 5"),
+        Limits {
+            depth: 5,
+            time: None
+        }
+    );
+    assert_eq!(
+        uci.limits("movetime 1500").time,
+        Some(Duration::from_millis(1500))
+    );
+    // a 30th of the clock of the side to move, plus half the increment
+    assert_eq!(
+        uci.limits("wtime 60000 btime 30000 winc 1000 binc 1000")
+            .time,
+        Some(Duration::from_millis(2500))
+    );
+    uci_session(&mut uci, &["position startpos moves e2e4"])?;
+    assert_eq!(
+        uci.limits("wtime 60000 btime 30000 movestogo 10").time,
+        Some(Duration::from_millis(3000))
+    );
+    // never more than the time left
+    assert_eq!(
+        uci.limits("btime 1000 binc 5000").time,
+        Some(Duration::from_millis(950))
+    );
+    Ok(())
+}
+
+#[test]
+fn uci_hash() -> Result<(), Box<dyn Error>> {
+    let mut uci = Uci::default();
+    let out = uci_session(&mut uci, &["uci"])?;
+    assert!(out.contains("option name Hash type spin default 16 min 0 max 4096\n"));
+    let default = uci.engine().table().capacity();
+
+    uci_session(&mut uci, &["setoption name Hash value 1"])?;
+    assert!(uci.engine().table().capacity() < default);
+    uci_session(&mut uci, &["setoption name Hash value 0"])?;
+    assert_eq!(uci.engine().table().capacity(), 0);
+    // a search still works without a table
+    let out = uci_session(&mut uci, &["position startpos", "go depth 2"])?;
+    assert!(out.contains("bestmove "));
+
+    let out = uci_session(
+        &mut uci,
+        &[
+            "setoption name Hash value lots",
+            "setoption name Threads value 4",
+        ],
+    )?;
+    assert_eq!(
+        out,
+        "info string invalid Hash `lots`\ninfo string unknown option `Threads`\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn xboard_handshake() -> Result<(), Box<dyn Error>> {
+    let out = xboard_session(&mut XBoard::default(), &["xboard", "protover 2", "ping 7"])?;
+    let lines: Vec<_> = out.lines().collect();
+    assert!(lines[0].starts_with("feature myname=\"rusty-chess"));
+    assert!(lines[0].contains("usermove=1") && lines[0].ends_with("done=1"));
+    assert!(lines[0].contains("memory=1"));
+    assert_eq!(lines[1], "pong 7");
+    Ok(())
+}
+
+#[test]
+fn xboard_force() -> Result<(), Box<dyn Error>> {
+    let mut xboard = XBoard::default();
+    let out = xboard_session(
+        &mut xboard,
+        &[
+            "new",
+            "force",
+            "usermove e2e4",
+            "usermove e7e5",
+            "usermove g1f3",
+        ],
+    )?;
+    // the engine doesn't answer in force mode
+    assert_eq!(out, "");
+    assert_eq!(
+        xboard.game().to_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+
+    xboard_session(&mut xboard, &["undo"])?;
+    assert_eq!(xboard.game().ply, 2);
+    xboard_session(&mut xboard, &["remove"])?;
+    assert_eq!(xboard.game().to_fen(), START_FEN);
+    Ok(())
+}
+
+#[test]
+fn xboard_usermove() -> Result<(), Box<dyn Error>> {
+    let mut xboard = XBoard::default();
+    let out = xboard_session(&mut xboard, &["new", "sd 1", "usermove e2e4"])?;
+    // the engine plays Black after `new`
+    assert!(out.starts_with("move "));
+    assert_eq!(xboard.game().turn, Color::White);
+
+    let out = xboard_session(&mut xboard, &["usermove e2e4"])?;
+    assert_eq!(out, "Illegal move: e2e4\n");
+
+    // a `?` read before the move doesn't cut the search short
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut xboard = XBoard::new(Arc::clone(&stop));
+    xboard_session(&mut xboard, &["new", "sd 2", "post"])?;
+    stop.store(true, Ordering::Relaxed);
+    let out = xboard_session(&mut xboard, &["usermove e2e4"])?;
+    assert!(out.lines().any(|line| line.starts_with("2 ")), "{out}");
+    Ok(())
+}
+
+#[test]
+fn xboard_go() -> Result<(), Box<dyn Error>> {
+    let mut xboard = XBoard::default();
+    let out = xboard_session(
+        &mut xboard,
+        &[
+            "new",
+            "force",
+            "setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            "sd 3",
+            "post",
+            "go",
+        ],
+    )?;
+    let lines: Vec<_> = out.lines().collect();
+    assert!(lines[0].starts_with("1 100001 ") && lines[0].ends_with(" a1a8"));
+    assert_eq!(lines[1..], ["move a1a8", "1-0 {White mates}"]);
+
+    let out = xboard_session(&mut xboard, &["usermove g8h8"])?;
+    assert!(out.starts_with("Illegal move (game over)"));
+
+    // the Knight takes the Queen
+    let out = xboard_session(
+        &mut xboard,
+        &[
+            "new",
+            "force",
+            "setboard 4k3/8/8/3q4/8/2N5/8/4K3 w - - 0 1",
+            "sd 2",
+            "go",
+        ],
+    )?;
+    let lines: Vec<_> = out.lines().collect();
+    let score: i32 = lines[1]
+        .strip_prefix("2 ")
+        .and_then(|thinking| thinking.split_whitespace().next())
+        .ok_or(lines[1])?
+        .parse()?;
+    // a Knight up, give or take where the pieces stand
+    assert!((score - 300).abs() < 100, "{score}");
+    // only the Knight is left
+    assert_eq!(lines[2..], ["move c3d5", "1/2-1/2 {Draw}"]);
+    Ok(())
+}
+
+#[test]
+fn xboard_memory() -> Result<(), Box<dyn Error>> {
+    let mut xboard = XBoard::default();
+    let default = xboard.engine().table().capacity();
+    xboard_session(&mut xboard, &["memory 1"])?;
+    assert!(xboard.engine().table().capacity() < default);
+
+    let out = xboard_session(&mut xboard, &["memory some"])?;
+    assert_eq!(out, "Error (invalid memory): some\n");
+    assert!(xboard.engine().table().capacity() > 0);
+    Ok(())
+}
+
+#[test]
+fn xboard_setboard() -> Result<(), Box<dyn Error>> {
+    let mut xboard = XBoard::default();
+    let out = xboard_session(&mut xboard, &["setboard not a position"])?;
+    assert!(out.starts_with("tellusererror Illegal position"));
+    assert_eq!(xboard.game().to_fen(), START_FEN);
+    Ok(())
+}
+
+#[test]
+fn xboard_limits() -> Result<(), Box<dyn Error>> {
+    let mut xboard = XBoard::default();
+    xboard_session(&mut xboard, &["level 0 5 2", "time 30000"])?;
+    // a 30th of the clock, plus half the increment
+    assert_eq!(xboard.limits().time, Some(Duration::from_millis(11000)));
+
+    // 39 moves left in the session after the first one
+    xboard_session(&mut xboard, &["force", "usermove e2e4", "usermove e7e5"])?;
+    xboard_session(&mut xboard, &["level 40 5 0", "time 3900"])?;
+    assert_eq!(xboard.limits().time, Some(Duration::from_secs(1)));
+
+    xboard_session(&mut xboard, &["st 2", "sd 4"])?;
+    assert_eq!(
+        xboard.limits(),
+        Limits {
+            depth: 4,
+            time: Some(Duration::from_secs(2))
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,300 @@
+//! The [Universal Chess Interface](https://www.chessprogramming.org/UCI):
+//! the GUI sets up positions with `position` and asks for moves with `go`,
+//! the engine answers with `info` lines and a `bestmove`.
+//!
+//! The search runs on its own thread, so `isready` and `stop` are answered while it thinks.
+
+use {
+    crate::{
+        engine::{DEFAULT_HASH, Engine, Limits, MATE, MAX_HASH, SearchResult},
+        game::Game,
+        protocol::{MAX_DEPTH, MOVES_TO_GO, read_lines, time_for_move},
+        types::*,
+    },
+    std::{
+        error::Error,
+        io::{self, Write},
+        sync::{
+            Arc, Mutex, MutexGuard,
+            atomic::{AtomicBool, Ordering},
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+/// State of a UCI session, answering to `out`.
+#[derive(Debug)]
+pub struct Uci<W = io::Stdout> {
+    game: Game,
+    /// Kept between the searches of a game for its transposition table,
+    /// stopped by `stop` and `quit` (see [`Engine::with_stop`])
+    engine: Arc<Mutex<Engine>>,
+    stop: Arc<AtomicBool>,
+    /// Shared with the thread of the search
+    out: Arc<Mutex<W>>,
+    /// The search running, see [`Uci::wait`]
+    search: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl<W: Write + Send + Default + 'static> Default for Uci<W> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default(), W::default())
+    }
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    #[inline]
+    pub fn new(stop: Arc<AtomicBool>, out: W) -> Self {
+        Self {
+            game: Game::default(),
+            engine: Arc::new(Mutex::new(
+                Engine::new(Limits::default()).with_stop(Arc::clone(&stop)),
+            )),
+            stop,
+            out: Arc::new(Mutex::new(out)),
+            search: None,
+        }
+    }
+    /// The position set up by the last `position` command.
+    #[inline]
+    pub const fn game(&self) -> &Game {
+        &self.game
+    }
+    /// The engine searching the moves, once the search running is over.
+    #[inline]
+    pub fn engine(&self) -> MutexGuard<'_, Engine> {
+        self.engine.lock().unwrap()
+    }
+    /// Where the answers are written.
+    #[inline]
+    pub const fn output(&self) -> &Arc<Mutex<W>> {
+        &self.out
+    }
+    /// Runs a command, writing the answers to the output.
+    ///
+    /// Returns `false` when the session has to end (`quit`).
+    pub fn command(&mut self, line: &str) -> io::Result<bool> {
+        let (command, args) = line
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((line.trim(), ""));
+        // the other commands wait for the search to be over
+        if !matches!(command, "isready" | "stop" | "quit") {
+            self.wait()?;
+        }
+        let out = Arc::clone(&self.out);
+        let mut out = out.lock().unwrap();
+        match command {
+            "uci" => {
+                writeln!(out, "id name rusty-chess {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(out, "id author {}", env!("CARGO_PKG_AUTHORS"))?;
+                writeln!(
+                    out,
+                    "option name Hash type spin default {DEFAULT_HASH} min 0 max {MAX_HASH}"
+                )?;
+                writeln!(out, "uciok")?;
+            }
+            "isready" => writeln!(out, "readyok")?,
+            "setoption" => {
+                if let Err(e) = self.set_option(args) {
+                    writeln!(out, "info string {e}")?;
+                }
+            }
+            "ucinewgame" => {
+                self.game = Game::default();
+                self.engine().clear_hash();
+            }
+            "position" => {
+                if let Err(e) = self.position(args) {
+                    writeln!(out, "info string {e}")?;
+                }
+            }
+            "go" => {
+                drop(out);
+                self.go(args)?;
+                return Ok(true);
+            }
+            "stop" | "quit" => {
+                drop(out);
+                self.stop.store(true, Ordering::Relaxed);
+                if let Some(search) = &self.search {
+                    search.thread().unpark();
+                }
+                self.wait()?;
+                return Ok(command == "stop");
+            }
+            // the other commands aren't supported
+            _ => {}
+        }
+        out.flush()?;
+        Ok(true)
+    }
+    /// Waits for the search running to be over, after it has sent its `bestmove`.
+    pub fn wait(&mut self) -> io::Result<()> {
+        match self.search.take() {
+            Some(search) => search.join().expect("the search panicked"),
+            None => Ok(()),
+        }
+    }
+    /// `position startpos|fen <fen> [moves <move>...]`
+    fn position(&mut self, args: &str) -> Result<(), Box<dyn Error>> {
+        let (setup, moves) = args.split_once("moves").unwrap_or((args, ""));
+        let mut game = match setup.trim().split_once(char::is_whitespace) {
+            _ if setup.trim() == "startpos" => Game::default(),
+            Some(("fen", fen)) => Game::from_fen(fen.trim())?,
+            _ => return Err(format!("invalid position `{}`", args.trim()).into()),
+        };
+        for uci in moves.split_whitespace() {
+            let (mov, promotion) = Movement::from_uci(&game.board, game.turn, uci)?;
+            game.play(&mov, promotion);
+        }
+        self.game = game;
+        Ok(())
+    }
+    /// `setoption name <name> [value <value>]`, only `Hash` (in megabytes) is supported.
+    fn set_option(&mut self, args: &str) -> Result<(), Box<dyn Error>> {
+        let args = args.trim().strip_prefix("name").unwrap_or(args);
+        let (name, value) = args.split_once("value").unwrap_or((args, ""));
+        match name.trim() {
+            name if name.eq_ignore_ascii_case("Hash") => {
+                let megabytes = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|megabytes| *megabytes <= MAX_HASH)
+                    .ok_or_else(|| format!("invalid Hash `{}`", value.trim()))?;
+                self.engine().set_hash(megabytes);
+            }
+            name => return Err(format!("unknown option `{name}`").into()),
+        }
+        Ok(())
+    }
+    /// `go [depth <plies>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>]
+    /// [movestogo <moves>] [infinite] [ponder]`, searching on another thread
+    /// until the limits or `stop`.
+    ///
+    /// With `infinite` or `ponder`, the `bestmove` is sent only after `stop`,
+    /// even if the search ends before.
+    fn go(&mut self, args: &str) -> io::Result<()> {
+        // the previous search has to be over before its `stop` is forgotten
+        self.wait()?;
+        self.stop.store(false, Ordering::Relaxed);
+        let limits = self.limits(args);
+        let hold = args
+            .split_whitespace()
+            .any(|token| token == "infinite" || token == "ponder");
+        let (board, turn) = (self.game.board.clone(), self.game.turn);
+        let history = self.game.reversible_positions();
+        let halfmoves = self.game.move_count.into();
+        let (engine, stop, out) = (
+            Arc::clone(&self.engine),
+            Arc::clone(&self.stop),
+            Arc::clone(&self.out),
+        );
+
+        self.search = Some(thread::spawn(move || {
+            let start = Instant::now();
+            let mut engine = engine.lock().unwrap();
+            engine.set_limits(limits);
+            engine.set_history(history, halfmoves);
+
+            let mut written = Ok(());
+            let result = engine.search_with_info(&board, turn, |info| {
+                if written.is_ok() {
+                    let mut out = out.lock().unwrap();
+                    written = writeln!(out, "{}", Self::info(info, start.elapsed()))
+                        .and_then(|()| out.flush());
+                }
+            });
+            written?;
+            // woken up by `stop`
+            while hold && !stop.load(Ordering::Relaxed) {
+                thread::park();
+            }
+
+            let mut out = out.lock().unwrap();
+            match result {
+                Some(result) => {
+                    writeln!(out, "bestmove {}", result.movement.to_uci(result.promotion))?;
+                }
+                // the null move, as there's nothing to play
+                None => writeln!(out, "bestmove 0000")?,
+            }
+            out.flush()
+        }));
+        Ok(())
+    }
+    /// Limits of a search from the parameters of `go`.
+    ///
+    /// On a clock, the time is split as in [`time_for_move`].
+    pub fn limits(&self, args: &str) -> Limits {
+        let mut limits = Limits {
+            depth: MAX_DEPTH,
+            time: None,
+        };
+        let (mut time_left, mut increment, mut moves_to_go) = (None, 0, MOVES_TO_GO);
+
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let mut value = || tokens.next().and_then(|value| value.parse::<u64>().ok());
+            match (token, self.game.turn) {
+                ("depth", _) => limits.depth = value().map_or(MAX_DEPTH, |depth| depth as usize),
+                ("movetime", _) => limits.time = value().map(Duration::from_millis),
+                ("wtime", Color::White) | ("btime", Color::Black) => time_left = value(),
+                ("winc", Color::White) | ("binc", Color::Black) => {
+                    increment = value().unwrap_or_default();
+                }
+                ("movestogo", _) => {
+                    moves_to_go = value().map_or(MOVES_TO_GO, |moves| moves.max(1) as u32);
+                }
+                _ => {}
+            }
+        }
+        if limits.time.is_none()
+            && let Some(time_left) = time_left
+        {
+            limits.time = Some(time_for_move(
+                Duration::from_millis(time_left),
+                Duration::from_millis(increment),
+                moves_to_go,
+            ));
+        }
+        limits
+    }
+    /// `info` line of a completed depth.
+    fn info(result: &SearchResult, elapsed: Duration) -> String {
+        let score = if result.score.abs() >= MATE - MAX_DEPTH as i32 {
+            // in moves, negative when getting mated
+            let moves = (MATE - result.score.abs() + 1) / 2;
+            format!("mate {}", moves * result.score.signum())
+        } else {
+            format!("cp {}", result.score)
+        };
+        let millis = elapsed.as_millis().max(1);
+        format!(
+            "info depth {} score {score} nodes {} nps {} time {} pv {}",
+            result.depth,
+            result.nodes,
+            u128::from(result.nodes) * 1000 / millis,
+            elapsed.as_millis(),
+            result.movement.to_uci(result.promotion),
+        )
+    }
+}
+
+/// Speaks UCI over stdin and stdout until `quit`.
+pub fn run() -> io::Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let lines = read_lines(Arc::clone(&stop), |line| {
+        matches!(line.trim(), "stop" | "quit")
+    });
+    let mut uci = Uci::new(stop, io::stdout());
+    for line in lines {
+        if !uci.command(&line)? {
+            break;
+        }
+    }
+    Ok(())
+}
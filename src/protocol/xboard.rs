@@ -0,0 +1,271 @@
+//! The [Chess Engine Communication Protocol](https://www.gnu.org/software/xboard/engine-intf.html)
+//! of XBoard (version 2): the engine keeps the game, the GUI sends it the moves of the opponent
+//! (`usermove`) and the engine answers with its own (`move`) unless it is in `force` mode.
+
+use {
+    crate::{
+        engine::{Engine, Limits, MATE, MAX_HASH, SearchResult},
+        game::{Game, Outcome},
+        protocol::{MAX_DEPTH, MOVES_TO_GO, read_lines, time_for_move},
+        types::*,
+    },
+    std::{
+        io::{self, Write},
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Score of a mate in one move in the thinking output, a mate in `n` moves is `MATE_SCORE + n`
+const MATE_SCORE: i32 = 100_000;
+
+/// State of a CECP session.
+#[derive(Debug)]
+pub struct XBoard {
+    game: Game,
+    /// Color played by the engine, `None` in force mode
+    engine: Option<Color>,
+    /// Maximum depth (`sd`)
+    depth: Option<usize>,
+    /// Time for every move (`st`)
+    move_time: Option<Duration>,
+    /// Moves per time control (`level`), 0 if the whole game is played in the base time
+    moves_per_session: u32,
+    /// Increment per move (`level`)
+    increment: Duration,
+    /// Time left on the clock of the engine (`time`)
+    time_left: Option<Duration>,
+    /// Whether the thinking of the engine is shown (`post`)
+    post: bool,
+    /// Kept between the searches of a game for its transposition table,
+    /// stopped by `?` and `quit` (see [`Engine::with_stop`])
+    searcher: Engine,
+    /// Set to stop the search, cleared before the next one
+    stop: Arc<AtomicBool>,
+}
+
+impl Default for XBoard {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl XBoard {
+    #[inline]
+    pub fn new(stop: Arc<AtomicBool>) -> Self {
+        Self {
+            game: Game::default(),
+            engine: Some(Color::Black),
+            depth: None,
+            move_time: None,
+            moves_per_session: 0,
+            increment: Duration::ZERO,
+            time_left: None,
+            post: false,
+            searcher: Engine::new(Limits::default()).with_stop(Arc::clone(&stop)),
+            stop,
+        }
+    }
+    /// The game played in the session.
+    #[inline]
+    pub const fn game(&self) -> &Game {
+        &self.game
+    }
+    /// The engine searching the moves.
+    #[inline]
+    pub const fn engine(&self) -> &Engine {
+        &self.searcher
+    }
+    /// Runs a command, writing the answers to `out`.
+    ///
+    /// Returns `false` when the session has to end (`quit`).
+    pub fn command(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let (command, args) = line
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((line.trim(), ""));
+        let args = args.trim();
+        match command {
+            "protover" => writeln!(
+                out,
+                "feature myname=\"rusty-chess {}\" ping=1 setboard=1 usermove=1 \
+                colors=0 memory=1 sigint=0 sigterm=0 done=1",
+                env!("CARGO_PKG_VERSION")
+            )?,
+            "ping" => writeln!(out, "pong {args}")?,
+            "new" => {
+                self.game = Game::default();
+                self.engine = Some(Color::Black);
+                self.depth = None;
+                self.move_time = None;
+                self.searcher.clear_hash();
+            }
+            "force" => self.engine = None,
+            "go" => {
+                self.engine = Some(self.game.turn);
+                self.think(out)?;
+            }
+            "playother" => self.engine = Some(self.game.turn.opposite()),
+            "usermove" => self.user_move(args, out)?,
+            "undo" => {
+                self.game.undo();
+            }
+            "remove" => {
+                self.game.undo();
+                self.game.undo();
+            }
+            "result" => self.engine = None,
+            "setboard" => match Game::from_fen(args) {
+                Ok(game) => self.game = game,
+                Err(e) => writeln!(out, "tellusererror Illegal position: {e}")?,
+            },
+            "sd" => self.depth = args.parse().ok(),
+            "st" => self.move_time = args.parse().ok().map(Duration::from_secs_f64),
+            "level" => self.level(args),
+            "time" => {
+                // in centiseconds
+                self.time_left = args
+                    .parse()
+                    .ok()
+                    .map(|time: u64| Duration::from_millis(time * 10));
+            }
+            // the whole memory is given to the transposition table
+            "memory" => match args.parse::<usize>() {
+                Ok(megabytes) => self.searcher.set_hash(megabytes.min(MAX_HASH)),
+                Err(_) => writeln!(out, "Error (invalid memory): {args}")?,
+            },
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "quit" => return Ok(false),
+            // `xboard`, `?` and the other commands don't need an answer
+            _ => {}
+        }
+        out.flush()?;
+        Ok(true)
+    }
+    /// `usermove <move>`: plays the move of the opponent and answers it.
+    fn user_move(&mut self, uci: &str, out: &mut impl Write) -> io::Result<()> {
+        if self.game.outcome.is_some() {
+            return writeln!(out, "Illegal move (game over): {uci}");
+        }
+        match Movement::from_uci(&self.game.board, self.game.turn, uci) {
+            Ok((mov, promotion)) => {
+                self.play(&mov, promotion, out)?;
+                self.think(out)
+            }
+            Err(_) => writeln!(out, "Illegal move: {uci}"),
+        }
+    }
+    /// Plays a move, writing the result if it ends the game.
+    fn play(
+        &mut self,
+        mov: &Movement,
+        promotion: Option<char>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        self.game.play(mov, promotion);
+        self.game.outcome = self.game.end();
+        match self.game.outcome {
+            Some(Outcome::Win(color)) => {
+                writeln!(out, "{} {{{color:?} mates}}", Outcome::Win(color))
+            }
+            Some(Outcome::Draw) => writeln!(out, "{} {{Draw}}", Outcome::Draw),
+            None => Ok(()),
+        }
+    }
+    /// Searches and plays a move if it is the turn of the engine.
+    fn think(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if self.engine != Some(self.game.turn) || self.game.outcome.is_some() {
+            return Ok(());
+        }
+        // a `?` read before this search was meant for the previous one
+        self.stop.store(false, Ordering::Relaxed);
+        let start = Instant::now();
+        self.searcher.set_limits(self.limits());
+        self.searcher.set_history(
+            self.game.reversible_positions(),
+            self.game.move_count.into(),
+        );
+
+        let mut written = Ok(());
+        let result = self
+            .searcher
+            .search_with_info(&self.game.board, self.game.turn, |info| {
+                if self.post && written.is_ok() {
+                    written = writeln!(out, "{}", Self::thinking(info, start.elapsed()));
+                }
+            });
+        written?;
+        let Some(result) = result else {
+            return Ok(());
+        };
+        writeln!(out, "move {}", result.movement.to_uci(result.promotion))?;
+        self.play(&result.movement, result.promotion, out)
+    }
+    /// `level <moves per session> <base minutes[:seconds]> <increment seconds>`
+    fn level(&mut self, args: &str) {
+        let mut args = args.split_whitespace();
+        self.moves_per_session = args
+            .next()
+            .and_then(|moves| moves.parse().ok())
+            .unwrap_or_default();
+        // the base time only matters through the clock sent with `time`
+        args.next();
+        self.increment = args
+            .next()
+            .and_then(|inc| inc.parse().ok())
+            .map(Duration::from_secs_f64)
+            .unwrap_or_default();
+    }
+    /// Limits of the next search, from `sd`, `st`, and the clock set by `level` and `time`.
+    pub fn limits(&self) -> Limits {
+        let time = self.move_time.or_else(|| {
+            self.time_left.map(|time_left| {
+                let moves_to_go = match self.moves_per_session {
+                    0 => MOVES_TO_GO,
+                    moves => moves - (self.game.ply / 2) % moves,
+                };
+                time_for_move(time_left, self.increment, moves_to_go)
+            })
+        });
+        Limits {
+            depth: self.depth.unwrap_or(MAX_DEPTH),
+            time,
+        }
+    }
+    /// Thinking output of a completed depth: `<depth> <score> <centiseconds> <nodes> <moves>`.
+    fn thinking(result: &SearchResult, elapsed: Duration) -> String {
+        let score = if result.score.abs() >= MATE - MAX_DEPTH as i32 {
+            let moves = (MATE - result.score.abs() + 1) / 2;
+            (MATE_SCORE + moves) * result.score.signum()
+        } else {
+            result.score
+        };
+        format!(
+            "{} {score} {} {} {}",
+            result.depth,
+            elapsed.as_millis() / 10,
+            result.nodes,
+            result.movement.to_uci(result.promotion),
+        )
+    }
+}
+
+/// Speaks CECP over stdin and stdout until `quit`.
+pub fn run() -> io::Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let lines = read_lines(Arc::clone(&stop), |line| {
+        matches!(line.trim(), "?" | "quit")
+    });
+    let mut xboard = XBoard::new(stop);
+    for line in lines {
+        if !xboard.command(&line, &mut io::stdout().lock())? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -15,14 +15,14 @@ pub enum SpecialMove {
     /// only if it has never been moved.
     DoublePawn,
 
-    /// When the King and the furthest Rook have never been moved,
-    /// and there are no pieces between them, you can move the King by (-3, 0) and the Rook
-    /// next to it in the opposite direction.
+    /// When the King and the Rook on the A side have never been moved,
+    /// and there are no pieces between them, you can move the King to the C file and the Rook
+    /// next to it in the opposite direction (the D file).
     LongCastle,
 
-    /// When the King and the nearest Rook have never been moved,
-    /// and there are no pieces between them, you can move the King by (2, 0) and the Rook
-    /// next to it in the opposite direction.
+    /// When the King and the Rook on the H side have never been moved,
+    /// and there are no pieces between them, you can move the King to the G file and the Rook
+    /// next to it in the opposite direction (the F file).
     ShortCastle,
 }
 
@@ -57,6 +57,15 @@ impl Movement {
         }
     }
 
+    /// Whether the move is a [`SpecialMove::ShortCastle`] or a [`SpecialMove::LongCastle`].
+    #[inline]
+    pub const fn is_castle(&self) -> bool {
+        matches!(
+            self.special,
+            Some(SpecialMove::ShortCastle | SpecialMove::LongCastle)
+        )
+    }
+
     pub fn linear(&self) -> Option<Point> {
         let step = self.to - self.from;
         (step.x == 0 || step.y == 0 || step.x.abs() == step.y.abs())
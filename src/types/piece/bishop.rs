@@ -46,6 +46,10 @@ impl Piece for Bishop {
     fn score(&self) -> f64 {
         3.
     }
+    #[inline(always)]
+    fn symbol(&self) -> char {
+        'B'
+    }
     fn move_set(&self) -> IndexSet<Movement> {
         (1..Board::SIZE as isize)
             .flat_map(|i| Point::new(i, i).rotations())
@@ -17,8 +17,10 @@ use {
 /// It can't go in a square where it could get eaten.
 ///
 /// If it has never been moved, the King can do a castle with one of the Rooks
-/// moving the King (2, 0) (H1 rook) or (-2, 0) (A1 rook)
+/// moving the King to the G file (H1 rook) or to the C file (A1 rook)
 /// and the Rook next to the king in the opposite direction (if the rook has not been moved).
+/// In [Chess960](crate::types::chess960_rank) the King and the Rook land on the same files,
+/// wherever they start.
 /// #### Win conditions
 /// **Check**: When the King could get eaten the next turn if not moved.
 /// - When in **check**, you must protect the King someway.
@@ -65,6 +67,10 @@ impl Piece for King {
     fn score(&self) -> f64 {
         f64::INFINITY
     } // should not be called
+    #[inline(always)]
+    fn symbol(&self) -> char {
+        'K'
+    }
     fn is_state(&self, state: State) -> bool {
         matches!(state, State::PieceState(ps) if ps == self.state)
     }
@@ -74,12 +80,12 @@ impl Piece for King {
             .map(|(point, dir)| self.to_movement(point, None, dir))
             .chain([
                 self.to_movement(
-                    Point::new(-2, 0),
+                    Point::new(Self::LONG_CASTLE_FILE - self.pos.x, 0),
                     Some(SpecialMove::LongCastle),
                     Some(Direction::Left),
                 ),
                 self.to_movement(
-                    Point::new(2, 0),
+                    Point::new(Self::SHORT_CASTLE_FILE - self.pos.x, 0),
                     Some(SpecialMove::ShortCastle),
                     Some(Direction::Right),
                 ),
@@ -102,6 +108,10 @@ impl Piece for King {
 }
 
 impl King {
+    /// File where the King lands with a short castle (G)
+    pub const SHORT_CASTLE_FILE: isize = 6;
+    /// File where the King lands with a long castle (C)
+    pub const LONG_CASTLE_FILE: isize = 2;
     /// Constructor of King
     #[inline]
     pub fn new(color: Color, pos: Point) -> Self {
@@ -45,6 +45,10 @@ impl Piece for Knight {
     fn score(&self) -> f64 {
         3.
     }
+    #[inline(always)]
+    fn symbol(&self) -> char {
+        'N'
+    }
     fn move_set(&self) -> IndexSet<Movement> {
         Point::new(1, 2)
             .rotations()
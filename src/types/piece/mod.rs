@@ -16,11 +16,15 @@ use {
     std::{
         any::Any,
         fmt::{Debug, Display},
+        hash::{BuildHasher, RandomState},
+        time::SystemTime,
     },
 };
 
 /// A trait representing a Chess Piece.
-pub trait Piece: Display + Debug + Any {
+///
+/// Pieces are `Send`, so that a board can be searched on another thread.
+pub trait Piece: Display + Debug + Any + Send {
     /// Color of a given piece
     ///
     /// > This enforces the definition of a type that implements `Piece`
@@ -70,6 +74,10 @@ pub trait Piece: Display + Debug + Any {
     #[must_use]
     fn score(&self) -> f64;
 
+    /// Uppercase letter of the piece in algebraic notation (`P`, `N`, `B`, `R`, `Q` or `K`).
+    #[must_use]
+    fn symbol(&self) -> char;
+
     /// Returns `true` if the piece has the given state or `false` otherwise.
     /// If the piece has no state at all, `true` is returned.
     #[must_use]
@@ -100,7 +108,7 @@ pub trait Piece: Display + Debug + Any {
         Board::in_bounds(to).then_some(Movement::new(self.pos(), to, special, direction))
     }
 
-    /// An HashSet of all the piece's possible moves,
+    /// An IndexSet of all the piece's possible moves,
     /// not considering collisions.
     /// Use the Board for that.
     #[must_use]
@@ -141,17 +149,77 @@ pub trait Piece: Display + Debug + Any {
     #[must_use]
     fn clone_box(&self) -> Box<dyn Piece>;
 }
+/// Number of the starting positions of [Chess960](chess960_rank)
+pub const CHESS960_POSITIONS: usize = 960;
+/// Index of the classical setup (`RNBQKBNR`) among the [Chess960](chess960_rank) ones
+pub const CLASSICAL_SETUP: usize = 518;
+
+/// Letters (see [`Piece::symbol`]) of the pieces on the first row of the
+/// [Chess960](https://en.wikipedia.org/wiki/Fischer_random_chess) setup numbered `index`,
+/// from the `a` file to the `h` file.
+///
+/// The setups are numbered as in the
+/// [Scharnagl scheme](https://en.wikipedia.org/wiki/Fischer_random_chess_numbering_scheme):
+/// the Bishops are on squares of different colors and the King is between the Rooks.
+///
+/// Panics if `index` is not less than [`CHESS960_POSITIONS`].
+pub fn chess960_rank(index: usize) -> [char; Board::SIZE] {
+    assert!(index < CHESS960_POSITIONS, "Chess960 setup {index}");
+    /// Squares of the two Knights among the five left after the Bishops and the Queen
+    const KNIGHTS: [(usize, usize); 10] = [
+        (0, 1),
+        (0, 2),
+        (0, 3),
+        (0, 4),
+        (1, 2),
+        (1, 3),
+        (1, 4),
+        (2, 3),
+        (2, 4),
+        (3, 4),
+    ];
+
+    let mut rank = [' '; Board::SIZE];
+    let (index, light) = (index / 4, index % 4);
+    let (index, dark) = (index / 4, index % 4);
+    let (knights, queen) = (index / 6, index % 6);
+    rank[2 * light + 1] = 'B';
+    rank[2 * dark] = 'B';
+
+    // the n-th square still empty
+    let empty = |rank: &[char; Board::SIZE], n: usize| {
+        (0..Board::SIZE).filter(|x| rank[*x] == ' ').nth(n).unwrap()
+    };
+    let x = empty(&rank, queen);
+    rank[x] = 'Q';
+    let (first, second) = KNIGHTS[knights];
+    let (first, second) = (empty(&rank, first), empty(&rank, second));
+    rank[first] = 'N';
+    rank[second] = 'N';
+    for piece in ['R', 'K', 'R'] {
+        let x = empty(&rank, 0);
+        rank[x] = piece;
+    }
+    rank
+}
+/// Index of a random [Chess960](chess960_rank) setup.
+pub fn random_chess960() -> usize {
+    // the keys of `RandomState` are random for each process
+    (RandomState::new().hash_one(SystemTime::now()) % CHESS960_POSITIONS as u64) as usize
+}
 /// Given an offset from the start of the board, returns
 /// the correct piece which should be in that spot.
+#[inline]
 pub fn placement(x: isize, color: Color) -> Option<Box<dyn Piece>> {
+    chess960_placement(CLASSICAL_SETUP, x, color)
+}
+/// Like [`placement`], for the [Chess960](chess960_rank) setup numbered `index`.
+pub fn chess960_placement(index: usize, x: isize, color: Color) -> Option<Box<dyn Piece>> {
     let pos = Point::new(x, color.first_row() as isize);
-    match x {
-        0 | 7 => Some(Box::new(Rook::new(color, pos))),
-        1 | 6 => Some(Box::new(Knight::new(color, pos))),
-        2 | 5 => Some(Box::new(Bishop::new(color, pos))),
-        3 => Some(Box::new(Queen::new(color, pos))),
-        4 => Some(Box::new(King::new(color, pos))),
-        _ => None,
+    match chess960_rank(index).get(usize::try_from(x).ok()?)? {
+        'K' => Some(Box::new(King::new(color, pos))),
+        'R' => Some(Box::new(Rook::new(color, pos))),
+        c => piece_from_char(*c, color, pos),
     }
 }
 /// Returns a `Box<dyn Piece>` from its [`char`] representation.
@@ -200,8 +268,8 @@ pub enum PawnState {
     /// can suffer from En Passant.
     JustDouble,
 
-    /// The pawn has already done a double move,
-    /// cannot do it anymore and cannot suffer an En Passant.
+    /// The pawn has already been moved,
+    /// cannot do a double move anymore and cannot suffer an En Passant.
     Already,
 }
 
@@ -54,6 +54,10 @@ impl Piece for Pawn {
         1.
     }
     #[inline(always)]
+    fn symbol(&self) -> char {
+        'P'
+    }
+    #[inline(always)]
     fn is_state(&self, state: State) -> bool {
         matches!(state, State::PawnState(ps) if ps == self.state)
     }
@@ -45,6 +45,10 @@ impl Piece for Queen {
     fn score(&self) -> f64 {
         9.
     }
+    #[inline(always)]
+    fn symbol(&self) -> char {
+        'Q'
+    }
     fn move_set(&self) -> IndexSet<Movement> {
         let rook = Rook::new(self.color, self.pos);
         let bishop = Bishop::new(self.color, self.pos);
@@ -46,6 +46,10 @@ impl Piece for Rook {
     fn score(&self) -> f64 {
         5.
     }
+    #[inline(always)]
+    fn symbol(&self) -> char {
+        'R'
+    }
     fn is_state(&self, state: State) -> bool {
         matches!(state, State::PieceState(ps) if ps == self.state)
     }
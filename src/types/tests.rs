@@ -1,4 +1,8 @@
-use {crate::prelude::*, indexmap::IndexSet, std::error::Error};
+use {
+    crate::prelude::*,
+    indexmap::IndexSet,
+    std::{collections::HashSet, error::Error},
+};
 
 #[test]
 fn pawn() -> Result<(), Box<dyn Error>> {
@@ -347,3 +351,26 @@ fn color_to_mul() {
     assert_eq!(Color::White.opposite().sign(), -1);
     assert_eq!(Color::Black.opposite().sign(), 1);
 }
+
+#[test]
+fn chess960_setups() {
+    let rank = |index| String::from_iter(chess960_rank(index));
+    assert_eq!(rank(CLASSICAL_SETUP), "RNBQKBNR");
+    assert_eq!(rank(0), "BBQNNRKR");
+    assert_eq!(rank(959), "RKRNNQBB");
+
+    let ranks: HashSet<String> = (0..CHESS960_POSITIONS).map(rank).collect();
+    assert_eq!(ranks.len(), CHESS960_POSITIONS);
+    for rank in ranks {
+        let files = |piece| {
+            rank.match_indices(piece)
+                .map(|(x, _)| x)
+                .collect::<Vec<_>>()
+        };
+        let (bishops, rooks, king) = (files('B'), files('R'), files('K'));
+        assert_ne!(bishops[0] % 2, bishops[1] % 2, "{rank}");
+        assert!(rooks[0] < king[0] && king[0] < rooks[1], "{rank}");
+        assert_eq!((files('Q').len(), files('N').len()), (1, 2), "{rank}");
+    }
+    assert!(random_chess960() < CHESS960_POSITIONS);
+}
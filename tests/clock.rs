@@ -0,0 +1,155 @@
+use {
+    rusty_chess::prelude::*,
+    std::{error::Error, time::Duration},
+};
+
+const fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn time_controls() -> Result<(), Box<dyn Error>> {
+    assert_eq!("15".parse::<TimeControl>()?, TimeControl::sudden_death(15));
+    assert_eq!("5+3".parse::<TimeControl>()?, TimeControl::fischer(5, 3));
+    assert_eq!("5d3".parse::<TimeControl>()?, TimeControl::bronstein(5, 3));
+
+    let classical: TimeControl = "40/90+30+30".parse()?;
+    assert_eq!(
+        classical.stages,
+        [
+            Stage {
+                moves: Some(40),
+                time: secs(90 * 60)
+            },
+            Stage {
+                moves: None,
+                time: secs(30 * 60)
+            },
+        ]
+    );
+    assert_eq!(classical.bonus, Bonus::Fischer(secs(30)));
+    assert_eq!("40/90+30".parse::<TimeControl>()?.bonus, Bonus::None);
+    assert_eq!("0.5+1".parse::<TimeControl>()?.stages[0].time, secs(30));
+
+    for control in ["15", "3+2", "5d3", "40/90+30+30", "40/120+20/60+30d5"] {
+        assert_eq!(control.parse::<TimeControl>()?.to_string(), control);
+    }
+    for invalid in ["", "0", "five", "40/90", "5+3d2", "5+3+1", "0/90+30", "5+x"] {
+        assert!(invalid.parse::<TimeControl>().is_err(), "{invalid}");
+    }
+    Ok(())
+}
+
+#[test]
+fn sudden_death() {
+    let mut clock = Clock::new(TimeControl::sudden_death(1));
+    assert!(clock.press(Color::White, secs(20)));
+    assert_eq!(clock.remaining(Color::White), secs(40));
+    assert_eq!(clock.remaining(Color::Black), secs(60));
+    assert_eq!(clock.moves_to_go(Color::White), None);
+
+    // the flag falls
+    assert!(!clock.press(Color::White, secs(40)));
+    assert!(clock.flagged(Color::White));
+    assert_eq!(clock.remaining(Color::White), Duration::ZERO);
+    assert!(!clock.flagged(Color::Black));
+}
+
+#[test]
+fn fischer() {
+    let mut clock = Clock::new(TimeControl::fischer(1, 2));
+    assert!(clock.press(Color::White, secs(10)));
+    assert_eq!(clock.remaining(Color::White), secs(52));
+    // the increment is added even to a quick move
+    assert!(clock.press(Color::White, Duration::ZERO));
+    assert_eq!(clock.remaining(Color::White), secs(54));
+}
+
+#[test]
+fn bronstein() {
+    let mut clock = Clock::new(TimeControl::bronstein(1, 2));
+    assert!(clock.press(Color::White, secs(1)));
+    assert_eq!(clock.remaining(Color::White), secs(60));
+    assert!(clock.press(Color::White, secs(10)));
+    assert_eq!(clock.remaining(Color::White), secs(52));
+}
+
+#[test]
+fn stages() -> Result<(), Box<dyn Error>> {
+    let mut clock = Clock::new("2/1+3/2+1".parse()?);
+    assert_eq!(clock.moves_to_go(Color::Black), Some(2));
+    assert!(clock.press(Color::Black, secs(10)));
+    assert_eq!(clock.moves_to_go(Color::Black), Some(1));
+    // the second stage starts after the second move
+    assert!(clock.press(Color::Black, secs(10)));
+    assert_eq!(clock.remaining(Color::Black), secs(40 + 120));
+    assert_eq!(clock.moves_to_go(Color::Black), Some(3));
+    for _ in 0..3 {
+        assert!(clock.press(Color::Black, secs(10)));
+    }
+    assert_eq!(clock.remaining(Color::Black), secs(130 + 60));
+    assert_eq!(clock.moves_to_go(Color::Black), None);
+    Ok(())
+}
+
+#[test]
+fn display() {
+    let mut clock = Clock::new(TimeControl::sudden_death(90));
+    assert_eq!(clock.to_string(), "White 1:30:00 | Black 1:30:00");
+    clock.press(Color::Black, secs(90 * 60 - 5) + Duration::from_millis(500));
+    assert_eq!(clock.to_string(), "White 1:30:00 | Black 0:04.5");
+}
+
+#[test]
+fn flag_fall() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1")?;
+    game.clock = Some(Clock::new(TimeControl::fischer(1, 1)));
+    assert!(game.press_clock(secs(30)));
+    assert_eq!(game.outcome, None);
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R b - - 0 1")?;
+    game.clock = Some(Clock::new(TimeControl::sudden_death(1)));
+    assert!(!game.press_clock(secs(61)));
+    assert_eq!(game.outcome, Some(Outcome::Win(Color::White)));
+    Ok(())
+}
+
+#[test]
+fn timeout_draw() -> Result<(), Box<dyn Error>> {
+    // the player still on time can't checkmate
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K2R w - - 0 1",
+        "q3k3/8/8/8/8/8/8/4K2N b - - 0 1",
+        "r3k3/8/8/8/8/8/8/1B2K3 b - - 0 1",
+        "4k3/8/8/8/8/3B4/8/1B1bK3 b - - 0 1",
+    ] {
+        let game = Game::from_fen(fen)?;
+        assert_eq!(game.timeout(game.turn), Outcome::Draw, "{fen}");
+    }
+    // it could, with the help of the other player
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K2R b - - 0 1",
+        "3rk3/8/8/8/8/8/8/4K2N b - - 0 1",
+        "4k3/8/8/8/8/8/8/1B1nK3 b - - 0 1",
+        "4k3/8/8/8/8/8/8/1BB1K3 b - - 0 1",
+        "4k3/7p/8/8/8/8/8/1B2K3 b - - 0 1",
+    ] {
+        let game = Game::from_fen(fen)?;
+        assert_eq!(
+            game.timeout(game.turn),
+            Outcome::Win(game.turn.opposite()),
+            "{fen}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn bot_limits() {
+    let mut game = Game::default();
+    assert_eq!(game.bot_limits(), game.limits);
+
+    game.clock = Some(Clock::new(TimeControl::fischer(1, 2)));
+    let time = game.bot_limits().time.unwrap();
+    assert!(time > Duration::ZERO && time < secs(60));
+}
@@ -44,3 +44,82 @@ fn fools_mate() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn legal_mate() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let movements = [
+        "e2e4", "e7e5", "g1f3", "d7d6", "f1c4", "c8g4", "b1c3", "g7g6", "f3e5", "g4d1", "c4f7",
+        "e8e7", "c3d5", // checkmate
+    ];
+
+    for mov in movements {
+        moves::play(&mut game, mov)?;
+    }
+
+    assert!(game.board.checkmate(Color::Black));
+    assert_eq!(game.end(), Some(Outcome::Win(Color::White)));
+
+    Ok(())
+}
+
+#[test]
+fn double_check() -> Result<(), Box<dyn Error>> {
+    // Be7 blocks the Rook and cxd6 eats the Knight, but neither stops both checks
+    let game = Game::from_fen("3qkb2/2pp1p2/3N4/8/8/8/8/4R1K1 b - - 0 1")?;
+    assert!(game.board.checkmate(Color::Black));
+    assert_eq!(game.end(), Some(Outcome::Win(Color::White)));
+
+    // with a single check, cxd6 saves the King
+    let game = Game::from_fen("3qkb2/2pp1p2/3N4/8/8/8/8/6K1 b - - 0 1")?;
+    assert!(!game.board.checkmate(Color::Black));
+    Ok(())
+}
+
+#[test]
+fn pinned_interposer() -> Result<(), Box<dyn Error>> {
+    // Bc8 would block the Rook, but the Bishop is pinned by Bc4
+    let game = Game::from_fen("R5k1/6pp/4b3/6N1/2B5/8/8/4K3 b - - 0 1")?;
+    assert!(game.board.checkmate(Color::Black));
+
+    // without the pin it can block
+    let game = Game::from_fen("R5k1/6pp/4b3/6N1/8/8/8/4K3 b - - 0 1")?;
+    assert!(!game.board.checkmate(Color::Black));
+    Ok(())
+}
+
+#[test]
+fn en_passant_escape() -> Result<(), Box<dyn Error>> {
+    // ...g5+ is only answered by hxg6 en passant
+    let mut game = Game::from_fen("k7/8/4bp2/6pP/7K/r7/8/8 w - g6 0 1")?;
+    assert!(game.board.check(Color::White).is_some());
+    assert!(!game.board.checkmate(Color::White));
+    assert_eq!(game.end(), None);
+    let moves: Vec<_> = game
+        .board
+        .legal_moves(Color::White)
+        .into_iter()
+        .map(|mov| mov.to_uci(None))
+        .collect();
+    assert_eq!(moves, ["h5g6"]);
+    moves::play(&mut game, "h5g6")?;
+
+    // once the En Passant is gone, it's mate
+    let game = Game::from_fen("k7/8/4bp2/6pP/7K/r7/8/8 w - - 0 1")?;
+    assert!(game.board.checkmate(Color::White));
+    Ok(())
+}
+
+#[test]
+fn stalemate_is_not_mate() -> Result<(), Box<dyn Error>> {
+    // the pawn is blocked and the King can only step into checks
+    let game = Game::from_fen("7k/8/8/8/8/p7/P1q5/K7 w - - 0 1")?;
+    assert!(!game.board.checkmate(Color::White));
+    assert!(game.board.stalemate(Color::White));
+    assert_eq!(game.end(), Some(Outcome::Draw));
+
+    // a pinned piece can't move either
+    let game = Game::from_fen("rr5k/8/8/8/8/8/B7/K7 w - - 0 1")?;
+    assert!(game.board.stalemate(Color::White));
+    Ok(())
+}
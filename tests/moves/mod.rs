@@ -20,3 +20,11 @@ pub fn do_move(
 
     Ok(mov)
 }
+
+/// Plays a move written in coordinates (e.g. `e2e4`, `e7e8q`) for the player whose turn it is.
+pub fn play(game: &mut Game, mov: &str) -> Result<Movement, Box<dyn Error>> {
+    let (mov, promotion) = Movement::from_uci(&game.board, game.turn, mov)?;
+    game.play(&mov, promotion);
+
+    Ok(mov)
+}
@@ -0,0 +1,137 @@
+use {rusty_chess::prelude::*, std::error::Error};
+
+// Positions and results from https://www.chessprogramming.org/Perft_Results
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str =
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+// Chess960 positions and results from https://www.chessprogramming.org/Chess960_Perft_Results
+const CHESS960_1: &str = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+const CHESS960_2: &str = "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9";
+const CHESS960_3: &str = "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9";
+const CHESS960_4: &str = "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9";
+
+fn assert_perft(fen: &str, nodes: &[u64]) -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen(fen)?;
+    for (depth, nodes) in nodes.iter().enumerate() {
+        assert_eq!(
+            game.perft(depth + 1),
+            *nodes,
+            "{fen} at depth {}",
+            depth + 1
+        );
+    }
+    // the board is left as it was
+    assert_eq!(game.to_fen(), Game::from_fen(fen)?.to_fen());
+    Ok(())
+}
+
+/// [`assert_perft`] with the [`BitBoard`] backend
+fn assert_bitboard_perft(fen: &str, nodes: &[u64]) -> Result<(), Box<dyn Error>> {
+    let game = Game::from_fen(fen)?;
+    let bitboard = BitBoard::from(&game.board);
+    for (depth, nodes) in nodes.iter().enumerate() {
+        assert_eq!(
+            bitboard.perft(game.turn, depth + 1),
+            *nodes,
+            "{fen} at depth {}",
+            depth + 1
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn initial_position() -> Result<(), Box<dyn Error>> {
+    assert_perft(START_FEN, &[20, 400])
+}
+#[test]
+fn kiwipete() -> Result<(), Box<dyn Error>> {
+    assert_perft(KIWIPETE, &[48])
+}
+#[test]
+fn position_3() -> Result<(), Box<dyn Error>> {
+    assert_perft(POSITION_3, &[14, 191, 2_812])
+}
+#[test]
+fn position_4() -> Result<(), Box<dyn Error>> {
+    assert_perft(POSITION_4, &[6, 264])
+}
+#[test]
+fn position_4_mirrored() -> Result<(), Box<dyn Error>> {
+    assert_perft(POSITION_4_MIRRORED, &[6, 264])
+}
+#[test]
+fn position_5() -> Result<(), Box<dyn Error>> {
+    assert_perft(POSITION_5, &[44, 1_486])
+}
+#[test]
+fn position_6() -> Result<(), Box<dyn Error>> {
+    assert_perft(POSITION_6, &[46, 2_079])
+}
+#[test]
+fn chess960() -> Result<(), Box<dyn Error>> {
+    assert_perft(CHESS960_1, &[21, 528, 12_189])?;
+    assert_perft(CHESS960_2, &[21, 807, 18_002])?;
+    assert_perft(CHESS960_3, &[20, 479, 10_471])?;
+    assert_perft(CHESS960_4, &[22, 593, 13_440])
+}
+
+#[test]
+fn divide() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen(POSITION_4)?;
+    let divide = game.divide(2);
+
+    assert_eq!(divide.len(), 6);
+    assert_eq!(divide.values().sum::<u64>(), 264);
+    // White is in check
+    assert_eq!(divide["c4c5"], 43);
+    assert_eq!(divide["g1h1"], 46);
+    assert!(divide.keys().is_sorted());
+    Ok(())
+}
+
+#[test]
+fn bitboard() -> Result<(), Box<dyn Error>> {
+    assert_bitboard_perft(START_FEN, &[20, 400, 8_902])?;
+    assert_bitboard_perft(KIWIPETE, &[48, 2_039])?;
+    assert_bitboard_perft(POSITION_3, &[14, 191, 2_812])?;
+    assert_bitboard_perft(POSITION_4, &[6, 264, 9_467])?;
+    assert_bitboard_perft(POSITION_4_MIRRORED, &[6, 264, 9_467])?;
+    assert_bitboard_perft(POSITION_5, &[44, 1_486])?;
+    assert_bitboard_perft(POSITION_6, &[46, 2_079])?;
+    assert_bitboard_perft(CHESS960_1, &[21, 528, 12_189])?;
+    assert_bitboard_perft(CHESS960_2, &[21, 807, 18_002])?;
+    assert_bitboard_perft(CHESS960_3, &[20, 479, 10_471])?;
+    assert_bitboard_perft(CHESS960_4, &[22, 593, 13_440])
+}
+
+// Deeper searches, run with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn deep() -> Result<(), Box<dyn Error>> {
+    assert_perft(START_FEN, &[20, 400, 8_902, 197_281])?;
+    assert_perft(KIWIPETE, &[48, 2_039, 97_862])?;
+    assert_perft(POSITION_3, &[14, 191, 2_812, 43_238, 674_624])?;
+    assert_perft(POSITION_4, &[6, 264, 9_467, 422_333])?;
+    assert_perft(POSITION_5, &[44, 1_486, 62_379])?;
+    assert_perft(POSITION_6, &[46, 2_079, 89_890])
+}
+#[test]
+#[ignore]
+fn deep_bitboard() -> Result<(), Box<dyn Error>> {
+    assert_bitboard_perft(START_FEN, &[20, 400, 8_902, 197_281, 4_865_609])?;
+    assert_bitboard_perft(KIWIPETE, &[48, 2_039, 97_862, 4_085_603])?;
+    assert_bitboard_perft(POSITION_3, &[14, 191, 2_812, 43_238, 674_624, 11_030_083])?;
+    assert_bitboard_perft(POSITION_4, &[6, 264, 9_467, 422_333, 15_833_292])?;
+    assert_bitboard_perft(POSITION_5, &[44, 1_486, 62_379, 2_103_487])?;
+    assert_bitboard_perft(POSITION_6, &[46, 2_079, 89_890, 3_894_594])?;
+    assert_bitboard_perft(CHESS960_1, &[21, 528, 12_189, 326_672])?;
+    assert_bitboard_perft(CHESS960_2, &[21, 807, 18_002, 667_366])?;
+    assert_bitboard_perft(CHESS960_3, &[20, 479, 10_471, 273_318])?;
+    assert_bitboard_perft(CHESS960_4, &[22, 593, 13_440, 382_958])
+}
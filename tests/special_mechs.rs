@@ -11,7 +11,7 @@ fn fifty_moves() -> Result<(), Box<dyn Error>> {
 
     for (i, (from, to)) in movements.take(50).enumerate() {
         moves::do_move(&mut game, from, to, None)?;
-        assert_eq!(game.move_count, i as u8 + 1);
+        assert_eq!(game.move_count, i as u16 + 1);
     }
 
     moves::do_move(&mut game, "E2", "E4", None)?;
@@ -19,3 +19,185 @@ fn fifty_moves() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn fifty_moves_capture() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    for mov in ["g1f3", "b8c6", "f3g1", "c6b8", "e2e4", "g8f6", "b1c3"] {
+        moves::play(&mut game, mov)?;
+    }
+    assert_eq!(game.move_count, 2);
+
+    moves::play(&mut game, "f6e4")?; // capture
+    assert_eq!(game.move_count, 0);
+
+    Ok(())
+}
+
+#[test]
+fn fifty_moves_chess960_castle() -> Result<(), Box<dyn Error>> {
+    // the King castles onto the square of its own Rook, which isn't a capture
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/5KR1 w G - 5 20")?;
+    let castle = game
+        .board
+        .filtered_move_set(Point::try_from("f1")?)
+        .into_iter()
+        .find(|mov| mov.special == Some(SpecialMove::ShortCastle))
+        .ok_or("no short castle")?;
+    assert_eq!(castle.to, Point::try_from("g1")?);
+
+    game.play(&castle, None);
+    assert_eq!(game.move_count, 6);
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 6 20");
+
+    Ok(())
+}
+
+#[test]
+fn fifty_moves_draw() -> Result<(), Box<dyn Error>> {
+    // the halfmove clock counts plies, the game is drawn after 100 of them
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 98 80")?;
+    assert_eq!(game.end(), None);
+
+    moves::play(&mut game, "a1a2")?;
+    assert_eq!(game.end(), None);
+
+    moves::play(&mut game, "e8d8")?;
+    assert_eq!(game.move_count, 100);
+    assert_eq!(game.end(), Some(Outcome::Draw));
+
+    Ok(())
+}
+
+#[test]
+fn undo_redo() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let mut fens = vec![game.to_fen()];
+    // capture, En Passant, castle and promotion
+    let movements = [
+        "e2e4", "d7d5", "e4d5", "c7c5", "d5c6", "d8d2", "c1d2", "g8f6", "c6b7", "e7e6", "b7a8n",
+        "f8e7", "d1g4", "e8g8",
+    ];
+    for mov in movements {
+        moves::play(&mut game, mov)?;
+        fens.push(game.to_fen());
+    }
+    let score = game.black_score();
+
+    for fen in fens.iter().rev().skip(1) {
+        assert!(game.undo());
+        assert_eq!(&game.to_fen(), fen);
+    }
+    assert!(!game.undo());
+    assert!(game.history.is_empty());
+    assert_eq!(game.black_score(), 0.);
+
+    for fen in fens.iter().skip(1) {
+        assert!(game.redo());
+        assert_eq!(&game.to_fen(), fen);
+    }
+    assert!(!game.redo());
+    assert_eq!(game.black_score(), score);
+    assert_eq!(game.history.len(), movements.len());
+
+    // a new move forgets the moves taken back
+    game.undo();
+    moves::play(&mut game, "e8d8")?;
+    assert!(!game.redo());
+
+    Ok(())
+}
+
+#[test]
+fn threefold_repetition() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let movements = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+    for mov in movements.iter().cycle().take(8) {
+        assert!(!game.can_claim_draw());
+        moves::play(&mut game, mov)?;
+    }
+    assert_eq!(game.repetitions(), 3);
+    assert!(game.can_claim_draw());
+    assert_eq!(game.end(), None);
+
+    game.undo();
+    assert!(!game.claim_draw());
+    game.redo();
+    assert!(game.claim_draw());
+    assert_eq!(game.outcome, Some(Outcome::Draw));
+
+    Ok(())
+}
+
+#[test]
+fn fivefold_repetition() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let movements = ["b1c3", "b8c6", "c3b1", "c6b8"];
+
+    for mov in movements.iter().cycle().take(15) {
+        moves::play(&mut game, mov)?;
+        assert_eq!(game.end(), None);
+    }
+    moves::play(&mut game, "c6b8")?;
+    assert_eq!(game.repetitions(), 5);
+    assert_eq!(game.end(), Some(Outcome::Draw));
+
+    Ok(())
+}
+
+#[test]
+fn repetition_en_passant() -> Result<(), Box<dyn Error>> {
+    // after e2e4 no black pawn can capture En Passant, so the position is the same
+    let mut game = Game::default();
+    for mov in ["e2e4", "g8f6", "g1f3", "f6g8", "f3g1"] {
+        moves::play(&mut game, mov)?;
+    }
+    assert_eq!(game.repetitions(), 2);
+
+    // after d2d4 the pawn on e4 can be captured, which changes the position
+    let mut game = Game::from_fen("rnbqkbnr/ppp1pppp/8/8/4p3/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")?;
+    for mov in ["d2d4", "g8f6", "g1f3", "f6g8", "f3g1"] {
+        moves::play(&mut game, mov)?;
+    }
+    assert_eq!(game.repetitions(), 1);
+
+    // exd6 would leave the King in check from the Rook, so d7d5 doesn't change the position
+    let mut game = Game::from_fen("7k/3p4/8/K3P2r/8/8/8/8 b - - 0 1")?;
+    for mov in ["d7d5", "a5a6", "h8g8", "a6a5", "g8h8"] {
+        moves::play(&mut game, mov)?;
+    }
+    assert_eq!(game.repetitions(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn insufficient_material() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/3q4/1N2K3 w - - 0 1")?;
+    assert_eq!(game.end(), None);
+
+    moves::play(&mut game, "e1d2")?; // only a Knight is left
+    assert_eq!(game.end(), Some(Outcome::Draw));
+
+    Ok(())
+}
+
+#[test]
+fn zobrist_transposition() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let mut other = Game::default();
+    for (mov, other_mov) in [("g1f3", "b1c3"), ("g8f6", "g8f6"), ("b1c3", "g1f3")] {
+        moves::play(&mut game, mov)?;
+        moves::play(&mut other, other_mov)?;
+    }
+    assert_eq!(game.zobrist(), other.zobrist());
+    assert_eq!(game.board.zobrist(), other.board.zobrist());
+
+    // same pieces, other player to move
+    let black = Game::from_fen(&game.to_fen().replace(" b ", " w "))?;
+    assert_eq!(black.board.zobrist(), game.board.zobrist());
+    assert_ne!(black.zobrist(), game.zobrist());
+
+    Ok(())
+}
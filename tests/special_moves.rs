@@ -121,3 +121,20 @@ fn en_passant() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn promote_in_one_move() -> Result<(), Box<dyn Error>> {
+    let mut game = Game::default();
+    let movements = [
+        "h2h4", "g7g5", "h4g5", "h7h6", "g5h6", "f8g7", "h6g7", "g8f6", "g7h8n", // promotion
+    ];
+
+    for mov in movements {
+        moves::play(&mut game, mov)?;
+    }
+
+    assert_presence::<Knight>(&game, Point::try_from("h8")?);
+    assert_empty(&game, Point::try_from("g7")?);
+
+    Ok(())
+}